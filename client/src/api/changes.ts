@@ -0,0 +1,33 @@
+import { api } from "./api";
+
+export type ChangeAction = "Create" | "Update" | "Delete";
+
+export interface Change {
+  sequence: number;
+  timestamp: string;
+  entity: string;
+  action: ChangeAction;
+  entity_id: string;
+  data: unknown | null;
+}
+
+export interface ChangesResponse {
+  changes: Change[];
+  cursor: number;
+}
+
+export class ChangesClient {
+  static async listChanges(
+    after: number,
+    wait = 0,
+    limit: number | null = null
+  ): Promise<ChangesResponse> {
+    const params = new URLSearchParams({
+      after: String(after),
+      wait: String(wait),
+    });
+    if (limit != null) params.set("limit", String(limit));
+    const response = await api(`changes?${params.toString()}`, "GET");
+    return response as ChangesResponse;
+  }
+}
@@ -1,4 +1,5 @@
 import { api } from "./api";
+import type { RankingDirection, RankingMethod } from "./ranking";
 import type { ScoreType } from "./scores";
 
 export interface CompetitionEvent {
@@ -10,6 +11,10 @@ export interface CompetitionEvent {
   enabled: boolean;
   score_type: ScoreType;
   calculator_config: string;
+  ranking_method: RankingMethod;
+  ranking_direction: RankingDirection;
+  verified_scores_only: boolean;
+  locked?: boolean;
 }
 
 export class CompetitionEventsClient {
@@ -47,4 +52,12 @@ export class CompetitionEventsClient {
   static async deleteCompetitionEvent(id: string): Promise<void> {
     await api(`competition_events/${encodeURIComponent(id)}`, "DELETE");
   }
+
+  static async lockCompetitionEvent(id: string): Promise<void> {
+    await api(`competition_events/${encodeURIComponent(id)}/lock`, "POST");
+  }
+
+  static async unlockCompetitionEvent(id: string): Promise<void> {
+    await api(`competition_events/${encodeURIComponent(id)}/unlock`, "POST");
+  }
 }
@@ -0,0 +1,46 @@
+import { api } from "./api";
+import type { ScoreType } from "./scores";
+
+export interface CompletenessEntry {
+  competition_event_id: string;
+  event_name: string;
+  score_type: ScoreType;
+  subject_id: string;
+  group_name: string;
+  team_name: string;
+  score_ids: string[];
+  missing: boolean;
+  conflicting: boolean;
+  disqualified: boolean;
+}
+
+export interface CompletenessReport {
+  entries: CompletenessEntry[];
+  missing: number;
+  conflicting: number;
+  disqualified: number;
+}
+
+export class CompletenessClient {
+  static async getSeasonCompleteness(
+    seasonId: string
+  ): Promise<CompletenessReport> {
+    const response = await api(
+      `completeness/season/${encodeURIComponent(seasonId)}`,
+      "GET"
+    );
+    return response as CompletenessReport;
+  }
+
+  static async getSeasonCompetitionCompleteness(
+    seasonCompetitionId: string
+  ): Promise<CompletenessReport> {
+    const response = await api(
+      `completeness/season_competition/${encodeURIComponent(
+        seasonCompetitionId
+      )}`,
+      "GET"
+    );
+    return response as CompletenessReport;
+  }
+}
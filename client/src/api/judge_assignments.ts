@@ -0,0 +1,51 @@
+import { api } from "./api";
+import type { CompetitionEvent } from "./competition_events";
+
+export interface JudgeAssignment {
+  id: string | null;
+  user_id: string;
+  competition_event_id: string | null;
+  season_competition_id: string | null;
+}
+
+export class JudgeAssignmentsClient {
+  static async listJudgeAssignments(): Promise<JudgeAssignment[]> {
+    const response = await api("judge_assignments", "GET");
+    return response as JudgeAssignment[];
+  }
+
+  static async listMyCompetitionEvents(): Promise<CompetitionEvent[]> {
+    const response = await api("judge_assignments/me", "GET");
+    return response as CompetitionEvent[];
+  }
+
+  static async getJudgeAssignment(id: string): Promise<JudgeAssignment> {
+    const response = await api(
+      `judge_assignments/${encodeURIComponent(id)}`,
+      "GET"
+    );
+    return response as JudgeAssignment;
+  }
+
+  static async createJudgeAssignment(
+    judgeAssignment: JudgeAssignment
+  ): Promise<string> {
+    const response = await api("judge_assignments", "POST", judgeAssignment);
+    return response as string;
+  }
+
+  static async updateJudgeAssignment(
+    id: string,
+    judgeAssignment: JudgeAssignment
+  ): Promise<void> {
+    await api(
+      `judge_assignments/${encodeURIComponent(id)}`,
+      "PUT",
+      judgeAssignment
+    );
+  }
+
+  static async deleteJudgeAssignment(id: string): Promise<void> {
+    await api(`judge_assignments/${encodeURIComponent(id)}`, "DELETE");
+  }
+}
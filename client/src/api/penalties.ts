@@ -0,0 +1,55 @@
+import { api } from "./api";
+import type { ScoreType } from "./scores";
+
+export type PenaltyType =
+  | "PointDeduction"
+  | "TimeAddition"
+  | "Disqualification";
+
+export interface Penalty {
+  id: string | null;
+  competition_event_id: string;
+  score_type: ScoreType;
+  subject_id: string;
+  penalty_code_id: string;
+  penalty_type: PenaltyType;
+  value: string | null;
+  notes: string | null;
+  created_by?: string | null;
+  created_at?: string | null;
+}
+
+export class PenaltiesClient {
+  static async listPenalties(): Promise<Penalty[]> {
+    const response = await api("penalties", "GET");
+    return response as Penalty[];
+  }
+
+  static async listPenaltiesInCompetitionEvent(
+    competitionEventId: string
+  ): Promise<Penalty[]> {
+    const response = await api(
+      `penalties/competition_event/${encodeURIComponent(competitionEventId)}`,
+      "GET"
+    );
+    return response as Penalty[];
+  }
+
+  static async getPenalty(id: string): Promise<Penalty> {
+    const response = await api(`penalties/${encodeURIComponent(id)}`, "GET");
+    return response as Penalty;
+  }
+
+  static async createPenalty(penalty: Penalty): Promise<string> {
+    const response = await api("penalties", "POST", penalty);
+    return response as string;
+  }
+
+  static async updatePenalty(id: string, penalty: Penalty): Promise<void> {
+    await api(`penalties/${encodeURIComponent(id)}`, "PUT", penalty);
+  }
+
+  static async deletePenalty(id: string): Promise<void> {
+    await api(`penalties/${encodeURIComponent(id)}`, "DELETE");
+  }
+}
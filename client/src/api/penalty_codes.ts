@@ -0,0 +1,39 @@
+import { api } from "./api";
+
+export interface PenaltyCode {
+  id: string | null;
+  code: string;
+  description: string;
+  enabled: boolean;
+}
+
+export class PenaltyCodesClient {
+  static async listPenaltyCodes(): Promise<PenaltyCode[]> {
+    const response = await api("penalty_codes", "GET");
+    return response as PenaltyCode[];
+  }
+
+  static async getPenaltyCode(id: string): Promise<PenaltyCode> {
+    const response = await api(
+      `penalty_codes/${encodeURIComponent(id)}`,
+      "GET"
+    );
+    return response as PenaltyCode;
+  }
+
+  static async createPenaltyCode(penaltyCode: PenaltyCode): Promise<string> {
+    const response = await api("penalty_codes", "POST", penaltyCode);
+    return response as string;
+  }
+
+  static async updatePenaltyCode(
+    id: string,
+    penaltyCode: PenaltyCode
+  ): Promise<void> {
+    await api(`penalty_codes/${encodeURIComponent(id)}`, "PUT", penaltyCode);
+  }
+
+  static async deletePenaltyCode(id: string): Promise<void> {
+    await api(`penalty_codes/${encodeURIComponent(id)}`, "DELETE");
+  }
+}
@@ -0,0 +1,69 @@
+import { api } from "./api";
+
+export type ProtestStatus = "Open" | "Upheld" | "Denied";
+
+export interface Protest {
+  id: string | null;
+  competition_event_id: string;
+  score_id: string | null;
+  reason: string;
+  filed_by?: string | null;
+  filed_at?: string | null;
+  status?: ProtestStatus;
+  resolution_notes?: string | null;
+  resolved_by?: string | null;
+  resolved_at?: string | null;
+}
+
+export class ProtestsClient {
+  static async listProtests(): Promise<Protest[]> {
+    const response = await api("protests", "GET");
+    return response as Protest[];
+  }
+
+  static async listProtestsInCompetitionEvent(
+    competitionEventId: string
+  ): Promise<Protest[]> {
+    const response = await api(
+      `protests/competition_event/${encodeURIComponent(competitionEventId)}`,
+      "GET"
+    );
+    return response as Protest[];
+  }
+
+  static async getProtest(id: string): Promise<Protest> {
+    const response = await api(`protests/${encodeURIComponent(id)}`, "GET");
+    return response as Protest;
+  }
+
+  static async createProtest(protest: Protest): Promise<string> {
+    const response = await api("protests", "POST", protest);
+    return response as string;
+  }
+
+  static async updateProtest(id: string, protest: Protest): Promise<void> {
+    await api(`protests/${encodeURIComponent(id)}`, "PUT", protest);
+  }
+
+  static async deleteProtest(id: string): Promise<void> {
+    await api(`protests/${encodeURIComponent(id)}`, "DELETE");
+  }
+
+  static async upholdProtest(
+    id: string,
+    resolutionNotes: string | null
+  ): Promise<void> {
+    await api(`protests/${encodeURIComponent(id)}/uphold`, "POST", {
+      resolution_notes: resolutionNotes,
+    });
+  }
+
+  static async denyProtest(
+    id: string,
+    resolutionNotes: string | null
+  ): Promise<void> {
+    await api(`protests/${encodeURIComponent(id)}/deny`, "POST", {
+      resolution_notes: resolutionNotes,
+    });
+  }
+}
@@ -0,0 +1,3 @@
+export type RankingMethod = "Standard" | "Dense" | "Fractional";
+
+export type RankingDirection = "HighestWins" | "LowestWins";
@@ -1,7 +1,10 @@
 import { api } from "./api";
+import type { Change } from "./changes";
 
 export type ScoreType = "Group" | "Team";
 
+export type VerificationState = "Entered" | "Verified" | "Rejected";
+
 export interface Score {
   id: string | null;
   competition_event_id: string;
@@ -12,12 +15,172 @@ export interface Score {
   valid: boolean;
   disqualified: boolean;
   notes: string | null;
+  original_id?: string | null;
+  revision?: number;
+  entered_by?: string | null;
+  entered_at?: string | null;
+  modified_by?: string | null;
+  modified_at?: string | null;
+  verification_state?: VerificationState;
+  verified_by?: string | null;
+  verified_at?: string | null;
+  judge_id?: string | null;
+}
+
+export interface ScoreRevision extends Score {
+  changed_fields: string[];
+  changed_score_data: string[];
+}
+
+export interface ScoreFilter {
+  season_id?: string | null;
+  season_competition_id?: string | null;
+  competition_event_id?: string | null;
+  group_participation_id?: string | null;
+  team_id?: string | null;
+  score_type?: ScoreType | null;
+  valid?: boolean | null;
+  disqualified?: boolean | null;
+  entered_after?: string | null;
+  entered_before?: string | null;
+  user_id?: string | null;
+  entered_by?: string | null;
+  modified_by?: string | null;
+  judge_id?: string | null;
+}
+
+export type ScoreSort = "Timestamp" | "EnteredAt" | "ModifiedAt";
+
+export type SortDirection = "Ascending" | "Descending";
+
+export interface ScorePagination {
+  sort?: ScoreSort;
+  direction?: SortDirection;
+  cursor?: string | null;
+  limit?: number | null;
+}
+
+export interface ScorePage {
+  scores: Score[];
+  cursor: string | null;
+}
+
+export type ScoreSyncAction = "Create" | "Update" | "Delete";
+
+export interface ScoreSyncOperation {
+  idempotency_key: string;
+  action: ScoreSyncAction;
+  id: string;
+  score?: Score | null;
+  revision?: number | null;
+}
+
+export type ScoreSyncStatus = "Success" | "Conflict" | "Failed";
+
+export interface ScoreValidationError {
+  errors: string[];
+  field_errors: Record<string, string>;
+}
+
+export interface ScoreSyncResult {
+  idempotency_key: string;
+  id: string;
+  status: ScoreSyncStatus;
+  error: string | null;
+  validation_error: ScoreValidationError | null;
+  score: Score | null;
+}
+
+export interface ScoreSyncResponse {
+  results: ScoreSyncResult[];
+  changes: Change[];
+  sync_token: number;
+  more_changes: boolean;
+}
+
+export type ScoreImportStatus = "Matched" | "Unmatched" | "Invalid";
+
+export interface ScoreImportRow {
+  row: number;
+  name: string;
+  group_name: string | null;
+  status: ScoreImportStatus;
+  score: Score | null;
+  errors: string[];
+  field_errors: Record<string, string>;
+}
+
+export interface ScoreImport {
+  competition_event_id: string;
+  errors: string[];
+  rows: ScoreImportRow[];
+  matched: number;
+  unmatched: number;
+  invalid: number;
+  created: string[] | null;
 }
 
 export class ScoresClient {
-  static async listScores(): Promise<Score[]> {
-    const response = await api("scores", "GET");
-    return response as Score[];
+  static async listScores(
+    filter: ScoreFilter = {},
+    pagination: ScorePagination = {}
+  ): Promise<ScorePage> {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries({ ...filter, ...pagination })) {
+      if (value != null) params.set(key, String(value));
+    }
+    const query = params.toString();
+    const response = await api(query ? `scores?${query}` : "scores", "GET");
+    return response as ScorePage;
+  }
+
+  static async listAllScores(filter: ScoreFilter = {}): Promise<Score[]> {
+    const scores: Score[] = [];
+    let cursor: string | null = null;
+    do {
+      const page: ScorePage = await ScoresClient.listScores(filter, {
+        cursor,
+        limit: 1000,
+      });
+      scores.push(...page.scores);
+      cursor = page.cursor;
+    } while (cursor != null);
+    return scores;
+  }
+
+  static async syncScores(
+    operations: ScoreSyncOperation[],
+    syncToken: number
+  ): Promise<ScoreSyncResponse> {
+    const response = await api("scores/sync", "POST", {
+      operations,
+      sync_token: syncToken,
+    });
+    return response as ScoreSyncResponse;
+  }
+
+  static async previewScoreImport(
+    competitionEventId: string,
+    csv: string
+  ): Promise<ScoreImport> {
+    const response = await api(
+      `scores/import/${encodeURIComponent(competitionEventId)}/preview`,
+      "POST",
+      { csv }
+    );
+    return response as ScoreImport;
+  }
+
+  static async importScores(
+    competitionEventId: string,
+    csv: string
+  ): Promise<ScoreImport> {
+    const response = await api(
+      `scores/import/${encodeURIComponent(competitionEventId)}`,
+      "POST",
+      { csv }
+    );
+    return response as ScoreImport;
   }
 
   static async getScore(id: string): Promise<Score> {
@@ -25,6 +188,14 @@ export class ScoresClient {
     return response as Score;
   }
 
+  static async getScoreHistory(id: string): Promise<ScoreRevision[]> {
+    const response = await api(
+      `scores/${encodeURIComponent(id)}/history`,
+      "GET"
+    );
+    return response as ScoreRevision[];
+  }
+
   static async createScore(score: Score): Promise<string> {
     const response = await api("scores", "POST", score);
     return response as string;
@@ -38,6 +209,24 @@ export class ScoresClient {
     await api(`scores/${encodeURIComponent(id)}`, "DELETE");
   }
 
+  static async verifyScore(id: string): Promise<void> {
+    await api(`scores/${encodeURIComponent(id)}/verify`, "POST");
+  }
+
+  static async rejectScore(id: string): Promise<void> {
+    await api(`scores/${encodeURIComponent(id)}/reject`, "POST");
+  }
+
+  static async getScoresAwaitingVerification(
+    competitionEventId: string
+  ): Promise<Score[]> {
+    const response = await api(
+      `scores/awaiting_verification/${competitionEventId}`,
+      "GET"
+    );
+    return response as Score[];
+  }
+
   static async getScoresForGroup(
     groupParticipationId: string,
     competitionEventId: string
@@ -1,4 +1,5 @@
 import { api } from "./api";
+import type { RankingDirection, RankingMethod } from "./ranking";
 
 export interface SeasonCompetition {
   id: string | null;
@@ -8,6 +9,9 @@ export interface SeasonCompetition {
   score_calculator: string | null;
   calculator_config: string;
   enabled: boolean;
+  ranking_method: RankingMethod;
+  ranking_direction: RankingDirection;
+  locked?: boolean;
 }
 
 export class SeasonCompetitionsClient {
@@ -49,4 +53,12 @@ export class SeasonCompetitionsClient {
   static async deleteSeasonCompetition(id: string): Promise<void> {
     await api(`season_competitions/${encodeURIComponent(id)}`, "DELETE");
   }
+
+  static async lockSeasonCompetition(id: string): Promise<void> {
+    await api(`season_competitions/${encodeURIComponent(id)}/lock`, "POST");
+  }
+
+  static async unlockSeasonCompetition(id: string): Promise<void> {
+    await api(`season_competitions/${encodeURIComponent(id)}/unlock`, "POST");
+  }
 }
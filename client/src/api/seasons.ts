@@ -1,4 +1,5 @@
 import { api } from "./api";
+import type { RankingDirection, RankingMethod } from "./ranking";
 
 export interface Season {
   id: string | null;
@@ -7,6 +8,9 @@ export interface Season {
   score_calculator: string | null;
   calculator_config: string;
   enabled: boolean;
+  ranking_method: RankingMethod;
+  ranking_direction: RankingDirection;
+  locked?: boolean;
 }
 
 export class SeasonsClient {
@@ -32,4 +36,12 @@ export class SeasonsClient {
   static async deleteSeason(id: string): Promise<void> {
     await api(`seasons/${encodeURIComponent(id)}`, "DELETE");
   }
+
+  static async lockSeason(id: string): Promise<void> {
+    await api(`seasons/${encodeURIComponent(id)}/lock`, "POST");
+  }
+
+  static async unlockSeason(id: string): Promise<void> {
+    await api(`seasons/${encodeURIComponent(id)}/unlock`, "POST");
+  }
 }
@@ -13,6 +13,8 @@ export interface User {
   permission_view_results: boolean;
   permission_view_scores: boolean;
   permission_enter_scores: boolean;
+  permission_verify_scores: boolean;
+  permission_override_locks: boolean;
   permission_view_registration: boolean;
   permission_enter_registration: boolean;
 }
@@ -27,6 +29,8 @@ export class UserPermission {
   static SCORE_ENTRY = 1 << 5;
   static REGISTRATION_VIEW = 1 << 6;
   static REGISTRATION_ENTRY = 1 << 7;
+  static SCORE_VERIFY = 1 << 8;
+  static LOCK_OVERRIDE = 1 << 9;
 }
 
 export class UsersClient {
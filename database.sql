@@ -37,7 +37,10 @@ CREATE TABLE IF NOT EXISTS "seasons" (
     "description" TEXT NOT NULL DEFAULT "",
     "score_calculator" BLOB REFERENCES "score_calculators" ("id"),
     "calculator_config" TEXT NOT NULL DEFAULT '{}',
-    "enabled" INTEGER NOT NULL DEFAULT 1
+    "enabled" INTEGER NOT NULL DEFAULT 1,
+    "ranking_method" TEXT NOT NULL DEFAULT 'standard',
+    "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins',
+    "locked" INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE TABLE IF NOT EXISTS "competitions" (
@@ -55,6 +58,9 @@ CREATE TABLE IF NOT EXISTS "season_competitions" (
     "score_calculator" BLOB REFERENCES "score_calculators" ("id"),
     "calculator_config" TEXT NOT NULL DEFAULT '{}',
     "enabled" INTEGER NOT NULL DEFAULT 1,
+    "ranking_method" TEXT NOT NULL DEFAULT 'standard',
+    "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins',
+    "locked" INTEGER NOT NULL DEFAULT 0,
     UNIQUE("season_id", "competition_id")
 );
 
@@ -76,6 +82,10 @@ CREATE TABLE IF NOT EXISTS "competition_events" (
     "calculator_config" TEXT NOT NULL DEFAULT '{}',
     "enabled" INTEGER NOT NULL DEFAULT 1,
     "score_type" TEXT NOT NULL DEFAULT 'team',
+    "ranking_method" TEXT NOT NULL DEFAULT 'standard',
+    "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins',
+    "verified_scores_only" INTEGER NOT NULL DEFAULT 0,
+    "locked" INTEGER NOT NULL DEFAULT 0,
     UNIQUE("season_competition_id", "event_id")
 );
 
@@ -112,8 +122,19 @@ CREATE TABLE IF NOT EXISTS "group_scores" (
     "timestamp" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
     "valid" INTEGER NOT NULL DEFAULT 1,
     "disqualified" INTEGER NOT NULL DEFAULT 0,
-    "notes" TEXT
+    "notes" TEXT,
+    "original_id" BLOB REFERENCES "group_scores" ("id") ON DELETE CASCADE,
+    "revision" INTEGER NOT NULL DEFAULT 1,
+    "entered_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "entered_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "modified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "modified_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "verification_state" TEXT NOT NULL DEFAULT 'entered',
+    "verified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "verified_at" TEXT,
+    "judge_id" BLOB REFERENCES "users" ("id") ON DELETE SET NULL
 );
+CREATE INDEX IF NOT EXISTS "index__group_scores__original_id" ON "group_scores" ("original_id");
 CREATE INDEX IF NOT EXISTS "index__group_scores__competition_event_id__group_participation_id" ON "group_scores" ("competition_event_id", "group_participation_id");
 
 CREATE TABLE IF NOT EXISTS "team_scores" (
@@ -124,10 +145,84 @@ CREATE TABLE IF NOT EXISTS "team_scores" (
     "timestamp" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
     "valid" INTEGER NOT NULL DEFAULT 1,
     "disqualified" INTEGER NOT NULL DEFAULT 0,
-    "notes" TEXT
+    "notes" TEXT,
+    "original_id" BLOB REFERENCES "team_scores" ("id") ON DELETE CASCADE,
+    "revision" INTEGER NOT NULL DEFAULT 1,
+    "entered_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "entered_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "modified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "modified_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "verification_state" TEXT NOT NULL DEFAULT 'entered',
+    "verified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "verified_at" TEXT,
+    "judge_id" BLOB REFERENCES "users" ("id") ON DELETE SET NULL
 );
+CREATE INDEX IF NOT EXISTS "index__team_scores__original_id" ON "team_scores" ("original_id");
 CREATE INDEX IF NOT EXISTS "index__team_scores__competition_event_id__team_id" ON "team_scores" ("competition_event_id", "team_id");
 
+CREATE TABLE IF NOT EXISTS "judge_assignments" (
+    "id" BLOB PRIMARY KEY NOT NULL DEFAULT (randomblob(16)),
+    "user_id" BLOB NOT NULL REFERENCES "users" ("id") ON DELETE CASCADE,
+    "competition_event_id" BLOB REFERENCES "competition_events" ("id") ON DELETE CASCADE,
+    "season_competition_id" BLOB REFERENCES "season_competitions" ("id") ON DELETE CASCADE,
+    CHECK (("competition_event_id" IS NULL) != ("season_competition_id" IS NULL))
+);
+CREATE INDEX IF NOT EXISTS "index__judge_assignments__user_id" ON "judge_assignments" ("user_id");
+
+CREATE TABLE IF NOT EXISTS "penalty_codes" (
+    "id" BLOB PRIMARY KEY NOT NULL DEFAULT (randomblob(16)),
+    "code" TEXT NOT NULL UNIQUE,
+    "description" TEXT NOT NULL DEFAULT "",
+    "enabled" INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE IF NOT EXISTS "penalties" (
+    "id" BLOB PRIMARY KEY NOT NULL DEFAULT (randomblob(16)),
+    "competition_event_id" BLOB NOT NULL REFERENCES "competition_events" ("id") ON DELETE CASCADE,
+    "score_type" TEXT NOT NULL,
+    "subject_id" BLOB NOT NULL,
+    "penalty_code_id" BLOB NOT NULL REFERENCES "penalty_codes" ("id"),
+    "penalty_type" TEXT NOT NULL,
+    "value" TEXT,
+    "notes" TEXT,
+    "created_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "created_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00'))
+);
+CREATE INDEX IF NOT EXISTS "index__penalties__competition_event_id" ON "penalties" ("competition_event_id");
+
+CREATE TABLE IF NOT EXISTS "protests" (
+    "id" BLOB PRIMARY KEY NOT NULL DEFAULT (randomblob(16)),
+    "competition_event_id" BLOB NOT NULL REFERENCES "competition_events" ("id") ON DELETE CASCADE,
+    "group_score_id" BLOB REFERENCES "group_scores" ("id") ON DELETE CASCADE,
+    "team_score_id" BLOB REFERENCES "team_scores" ("id") ON DELETE CASCADE,
+    "reason" TEXT NOT NULL,
+    "filed_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "filed_at" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "status" TEXT NOT NULL DEFAULT 'open',
+    "resolution_notes" TEXT,
+    "resolved_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL,
+    "resolved_at" TEXT
+);
+CREATE INDEX IF NOT EXISTS "index__protests__competition_event_id" ON "protests" ("competition_event_id");
+CREATE INDEX IF NOT EXISTS "index__protests__group_score_id" ON "protests" ("group_score_id");
+CREATE INDEX IF NOT EXISTS "index__protests__team_score_id" ON "protests" ("team_score_id");
+
+CREATE TABLE IF NOT EXISTS "score_sync_operations" (
+    "idempotency_key" BLOB PRIMARY KEY NOT NULL,
+    "user_id" BLOB NOT NULL REFERENCES "users" ("id") ON DELETE CASCADE,
+    "timestamp" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "result" TEXT
+);
+
+CREATE TABLE IF NOT EXISTS "changes" (
+    "sequence" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "timestamp" TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f+00:00')),
+    "entity" TEXT NOT NULL,
+    "action" TEXT NOT NULL,
+    "entity_id" BLOB NOT NULL,
+    "data" TEXT
+);
+
 
 CREATE TABLE IF NOT EXISTS "log" (
     "id" BLOB PRIMARY KEY NOT NULL DEFAULT (randomblob(16)),
@@ -0,0 +1,105 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, Instant};
+
+use crate::{
+    database::{changes::Change, users::UserPermission},
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+/// Default and maximum number of changes returned at once
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+/// Maximum number of seconds to wait for changes
+const MAX_WAIT_SECONDS: u64 = 60;
+
+/// How often to check for new changes while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(list_changes))
+}
+
+#[derive(Deserialize)]
+pub struct ChangesQuery {
+    /// Sequence number to resume after, usually the cursor returned by the last request
+    #[serde(default)]
+    after: i64,
+    limit: Option<i64>,
+    /// Number of seconds to wait for changes if there aren't any yet
+    #[serde(default)]
+    wait: u64,
+}
+
+#[derive(Serialize)]
+pub struct ChangesResponse {
+    changes: Vec<Change>,
+    /// Sequence number to pass as `after` to get the next changes
+    cursor: i64,
+}
+
+/// Lists changes after a sequence number, optionally long-polling until there are any
+pub async fn list_changes(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Query(query): Query<ChangesQuery>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::ANY) else {
+        return AuthToken::failure_response();
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let deadline = Instant::now() + Duration::from_secs(query.wait.min(MAX_WAIT_SECONDS));
+
+    let mut cursor = query.after;
+    loop {
+        let changes = state.changes_service.list_after(cursor, limit);
+        if let Some(last) = changes.last() {
+            cursor = last.sequence;
+        }
+
+        // changes the user isn't allowed to see are skipped, but still move the cursor
+        let changes: Vec<Change> = changes
+            .into_iter()
+            .filter(|change| can_view(current_user.permissions, &change.entity))
+            .collect();
+
+        if !changes.is_empty() || Instant::now() >= deadline {
+            return Json(ChangesResponse { changes, cursor }).into_response();
+        }
+
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Checks if a user with the specified permissions can view changes to an entity,
+/// using the same permissions as viewing the entity itself. Changes to unknown entities are hidden.
+pub fn can_view(permissions: i64, entity: &str) -> bool {
+    match entity {
+        "score_calculator"
+        | "season"
+        | "competition"
+        | "season_competition"
+        | "event"
+        | "competition_event"
+        | "group"
+        | "group_participation"
+        | "team"
+        | "penalty_code" => true,
+        "user" => permissions & UserPermission::USER_ADMIN != 0,
+        "judge_assignment" => permissions & UserPermission::SETUP_ADMIN != 0,
+        "score" | "protest" | "penalty" => {
+            permissions & (UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY) != 0
+        }
+        _ => false,
+    }
+}
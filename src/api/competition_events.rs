@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -23,6 +23,8 @@ pub fn route() -> Router<Arc<AppState>> {
         .route("/:id", get(get_competition_event))
         .route("/:id", put(update_competition_event))
         .route("/:id", delete(delete_competition_event))
+        .route("/:id/lock", post(lock_competition_event))
+        .route("/:id/unlock", post(unlock_competition_event))
 }
 
 pub async fn list_competition_events(
@@ -66,6 +68,10 @@ pub async fn create_competition_event(
 
     let result = state.competition_events_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_event_create",
@@ -94,6 +100,10 @@ pub async fn update_competition_event(
 
     let result = state.competition_events_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_event_update",
@@ -121,6 +131,10 @@ pub async fn delete_competition_event(
 
     let result = state.competition_events_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_event_delete",
@@ -135,3 +149,50 @@ pub async fn delete_competition_event(
         Err(err) => err.to_status_code().into_response(),
     }
 }
+
+pub async fn lock_competition_event(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_competition_event_locked(state, id, token, true)
+}
+
+pub async fn unlock_competition_event(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_competition_event_locked(state, id, token, false)
+}
+
+fn set_competition_event_locked(
+    state: Arc<AppState>,
+    id: Uuid,
+    token: AuthToken,
+    locked: bool,
+) -> Response {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.competition_events_service.set_locked(id, locked);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        if locked {
+            "competition_event_lock"
+        } else {
+            "competition_event_unlock"
+        },
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
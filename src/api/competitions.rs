@@ -66,6 +66,10 @@ pub async fn create_competition(
 
     let result = state.competitions_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_create",
@@ -94,6 +98,10 @@ pub async fn update_competition(
 
     let result = state.competitions_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_update",
@@ -121,6 +129,10 @@ pub async fn delete_competition(
 
     let result = state.competitions_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "competition_delete",
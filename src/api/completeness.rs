@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use uuid::Uuid;
+
+use crate::{database::users::UserPermission, helpers::auth_extractor::AuthToken, AppState};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/season/:id", get(get_season_completeness))
+        .route(
+            "/season_competition/:id",
+            get(get_season_competition_completeness),
+        )
+}
+
+pub async fn get_season_completeness(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    if state.seasons_service.get(id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let result = state.completeness_service.report_for_season(id);
+
+    Json(result).into_response()
+}
+
+pub async fn get_season_competition_completeness(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    if state.season_competitions_service.get(id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let result = state.completeness_service.report_for_season_competition(id);
+
+    Json(result).into_response()
+}
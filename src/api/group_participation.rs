@@ -66,6 +66,10 @@ pub async fn create_group_participation(
 
     let result = state.group_participations_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "group_participation_create",
@@ -94,6 +98,10 @@ pub async fn update_group_participation(
 
     let result = state.group_participations_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "group_participation_update",
@@ -121,6 +129,10 @@ pub async fn delete_group_participation(
 
     let result = state.group_participations_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_delete",
@@ -66,6 +66,10 @@ pub async fn create_group(
 
     let result = state.groups_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "group_create",
@@ -94,6 +98,10 @@ pub async fn update_group(
 
     let result = state.groups_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "group_update",
@@ -121,6 +129,10 @@ pub async fn delete_group(
 
     let result = state.groups_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "group_delete",
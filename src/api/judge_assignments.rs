@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    database::{judge_assignments::JudgeAssignment, users::UserPermission},
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_judge_assignments))
+        .route("/", post(create_judge_assignment))
+        .route("/me", get(list_my_competition_events))
+        .route("/:id", get(get_judge_assignment))
+        .route("/:id", put(update_judge_assignment))
+        .route("/:id", delete(delete_judge_assignment))
+}
+
+pub async fn list_judge_assignments(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.judge_assignments_service.list();
+
+    Json(result).into_response()
+}
+
+/// Lists the competition events the current user can enter scores for
+pub async fn list_my_competition_events(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = if current_user.permissions & UserPermission::SETUP_ADMIN != 0 {
+        state.competition_events_service.list()
+    } else {
+        state
+            .judge_assignments_service
+            .list_assigned_competition_events(current_user.id)
+    };
+
+    Json(result).into_response()
+}
+
+pub async fn get_judge_assignment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.judge_assignments_service.get(id);
+
+    match result {
+        Some(result) => Json(result).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+pub async fn create_judge_assignment(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<JudgeAssignment>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.judge_assignments_service.create(&request);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "judge_assignment_create",
+        json!({
+            "id": result.as_ref().ok(),
+            "judge_assignment": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(id) => Json(id).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn update_judge_assignment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<JudgeAssignment>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.judge_assignments_service.update(&request);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "judge_assignment_update",
+        json!({
+            "id": id,
+            "judge_assignment": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn delete_judge_assignment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.judge_assignments_service.delete(id);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "judge_assignment_delete",
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
@@ -1,9 +1,16 @@
 pub mod auth;
+pub mod changes;
 pub mod competition_events;
 pub mod competitions;
+pub mod completeness;
 pub mod events;
 pub mod group_participation;
 pub mod groups;
+pub mod judge_assignments;
+pub mod penalties;
+pub mod penalty_codes;
+pub mod protests;
+pub mod results;
 pub mod score_calculators;
 pub mod scores;
 pub mod season_competitions;
@@ -30,5 +37,12 @@ pub fn route() -> Router<Arc<AppState>> {
         .nest("/groups", groups::route())
         .nest("/group_participations", group_participation::route())
         .nest("/teams", teams::route())
+        .nest("/judge_assignments", judge_assignments::route())
         .nest("/scores", scores::route())
+        .nest("/protests", protests::route())
+        .nest("/penalty_codes", penalty_codes::route())
+        .nest("/penalties", penalties::route())
+        .nest("/results", results::route())
+        .nest("/completeness", completeness::route())
+        .nest("/changes", changes::route())
 }
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    database::{penalties::Penalty, users::UserPermission},
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_penalties))
+        .route("/", post(create_penalty))
+        .route("/:id", get(get_penalty))
+        .route("/:id", put(update_penalty))
+        .route("/:id", delete(delete_penalty))
+        .route(
+            "/competition_event/:competition_event_id",
+            get(list_penalties_in_competition_event),
+        )
+}
+
+pub async fn list_penalties(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalties_service.list();
+
+    Json(result).into_response()
+}
+
+pub async fn list_penalties_in_competition_event(
+    State(state): State<Arc<AppState>>,
+    Path(competition_event_id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .penalties_service
+        .list_in_competition_event(competition_event_id);
+
+    Json(result).into_response()
+}
+
+pub async fn get_penalty(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalties_service.get(id);
+
+    match result {
+        Some(result) => Json(result).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+pub async fn create_penalty(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<Penalty>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalties_service.create(&request, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_create",
+        json!({
+            "id": result.as_ref().ok(),
+            "penalty": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(id) => Json(id).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn update_penalty(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<Penalty>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalties_service.update(&request, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_update",
+        json!({
+            "id": id,
+            "penalty": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn delete_penalty(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalties_service.delete(id, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_delete",
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    database::{penalties::PenaltyCode, users::UserPermission},
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_penalty_codes))
+        .route("/", post(create_penalty_code))
+        .route("/:id", get(get_penalty_code))
+        .route("/:id", put(update_penalty_code))
+        .route("/:id", delete(delete_penalty_code))
+}
+
+pub async fn list_penalty_codes(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::ANY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalty_codes_service.list();
+
+    Json(result).into_response()
+}
+
+pub async fn get_penalty_code(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::ANY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalty_codes_service.get(id);
+
+    match result {
+        Some(result) => Json(result).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+pub async fn create_penalty_code(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<PenaltyCode>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalty_codes_service.create(&request);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_code_create",
+        json!({
+            "id": result.as_ref().ok(),
+            "penalty_code": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(id) => Json(id).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn update_penalty_code(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<PenaltyCode>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalty_codes_service.update(&request);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_code_update",
+        json!({
+            "id": id,
+            "penalty_code": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn delete_penalty_code(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.penalty_codes_service.delete(id);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "penalty_code_delete",
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
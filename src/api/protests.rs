@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        protests::{Protest, ProtestStatus},
+        users::UserPermission,
+    },
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_protests))
+        .route("/", post(create_protest))
+        .route("/:id", get(get_protest))
+        .route("/:id", put(update_protest))
+        .route("/:id", delete(delete_protest))
+        .route("/:id/uphold", post(uphold_protest))
+        .route("/:id/deny", post(deny_protest))
+        .route(
+            "/competition_event/:competition_event_id",
+            get(list_protests_in_competition_event),
+        )
+}
+
+pub async fn list_protests(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.list();
+
+    Json(result).into_response()
+}
+
+pub async fn list_protests_in_competition_event(
+    State(state): State<Arc<AppState>>,
+    Path(competition_event_id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .protests_service
+        .list_in_competition_event(competition_event_id);
+
+    Json(result).into_response()
+}
+
+pub async fn get_protest(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.get(id);
+
+    match result {
+        Some(result) => Json(result).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+pub async fn create_protest(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<Protest>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.create(&request, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "protest_create",
+        json!({
+            "id": result.as_ref().ok(),
+            "protest": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(id) => Json(id).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn update_protest(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<Protest>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.update(&request, &current_user);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "protest_update",
+        json!({
+            "id": id,
+            "protest": request,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ProtestResolution {
+    resolution_notes: Option<String>,
+}
+
+pub async fn uphold_protest(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<ProtestResolution>,
+) -> impl IntoResponse {
+    resolve_protest(
+        state,
+        id,
+        token,
+        ProtestStatus::Upheld,
+        request,
+        "protest_uphold",
+    )
+}
+
+pub async fn deny_protest(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<ProtestResolution>,
+) -> impl IntoResponse {
+    resolve_protest(
+        state,
+        id,
+        token,
+        ProtestStatus::Denied,
+        request,
+        "protest_deny",
+    )
+}
+
+fn resolve_protest(
+    state: Arc<AppState>,
+    id: Uuid,
+    token: AuthToken,
+    status: ProtestStatus,
+    request: ProtestResolution,
+    action: &str,
+) -> Response {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_VERIFY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.resolve(
+        id,
+        status,
+        request.resolution_notes.as_deref(),
+        &current_user,
+    );
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        action,
+        json!({
+            "id": id,
+            "resolution_notes": request.resolution_notes,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn delete_protest(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.protests_service.delete(id);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "protest_delete",
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+    },
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use mlua::Result as LuaResult;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::{
+    calculator::{CompetitionResult, EventResult, ResultsCalculator, SeasonResult},
+    database::users::UserPermission,
+    helpers::auth_extractor::AuthToken,
+    AppState,
+};
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/season/:id", get(get_season_results))
+        .route(
+            "/season_competition/:id",
+            get(get_season_competition_results),
+        )
+        .route("/competition_event/:id", get(get_competition_event_results))
+        .route("/generation", get(get_results_generation))
+        .route("/live", get(live_results))
+}
+
+/// Results to push to a live results connection.
+/// Browsers can't set headers on WebSocket connections, so the API token can also be passed as a query parameter.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LiveResultsQuery {
+    token: Option<String>,
+    season_id: Option<Uuid>,
+    season_competition_id: Option<Uuid>,
+    competition_event_id: Option<Uuid>,
+}
+
+/// Message pushed to live results connections
+#[derive(Serialize, Deserialize)]
+pub struct LiveResultsMessage {
+    generation: u64,
+    season: Option<SeasonResult>,
+    season_competition: Option<CompetitionResult>,
+    competition_event: Option<EventResult>,
+    error: Option<String>,
+}
+
+pub async fn get_season_results(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::RESULTS_VIEW) else {
+        return AuthToken::failure_response();
+    };
+
+    if state.seasons_service.get(id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    // calculating results runs scripts that may take a while, so don't block the async runtime
+    let calculation_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        calculation_state.results_cache.get_season(id, || {
+            ResultsCalculator::new(&calculation_state.database, &calculation_state.config)
+                .calculate_season(id)
+        })
+    })
+    .await
+    .expect("Error occurred calculating results");
+
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+pub async fn get_season_competition_results(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::RESULTS_VIEW) else {
+        return AuthToken::failure_response();
+    };
+
+    if state.season_competitions_service.get(id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let calculation_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        calculation_state
+            .results_cache
+            .get_season_competition(id, || {
+                ResultsCalculator::new(&calculation_state.database, &calculation_state.config)
+                    .calculate_season_competition(id)
+            })
+    })
+    .await
+    .expect("Error occurred calculating results");
+
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+pub async fn get_competition_event_results(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::RESULTS_VIEW) else {
+        return AuthToken::failure_response();
+    };
+
+    if state.competition_events_service.get(id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let calculation_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        calculation_state
+            .results_cache
+            .get_competition_event(id, || {
+                ResultsCalculator::new(&calculation_state.database, &calculation_state.config)
+                    .calculate_competition_event(id)
+            })
+    })
+    .await
+    .expect("Error occurred calculating results");
+
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+/// Gets the results cache generation, which changes whenever anything that could affect results changes
+pub async fn get_results_generation(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::RESULTS_VIEW) else {
+        return AuthToken::failure_response();
+    };
+
+    Json(state.results_cache.generation()).into_response()
+}
+
+/// WebSocket that pushes the requested season, season competition and/or competition event results
+/// when the connection is opened and again whenever anything that could affect results changes
+pub async fn live_results(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LiveResultsQuery>,
+    token: Option<AuthToken>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let Some(token) = token.or_else(|| query.token.clone().map(|token| AuthToken { token })) else {
+        return AuthToken::failure_response();
+    };
+    let Some(_current_user) = token.authorize(&state, UserPermission::RESULTS_VIEW) else {
+        return AuthToken::failure_response();
+    };
+
+    upgrade.on_upgrade(move |socket| push_live_results(state, token, query, socket))
+}
+
+async fn push_live_results(
+    state: Arc<AppState>,
+    token: AuthToken,
+    query: LiveResultsQuery,
+    mut socket: WebSocket,
+) {
+    let mut generations = state.results_cache.subscribe();
+
+    loop {
+        // stop pushing results if the session has expired or the user's permissions have changed
+        if token
+            .authorize(&state, UserPermission::RESULTS_VIEW)
+            .is_none()
+        {
+            break;
+        }
+
+        let (calculation_state, calculation_query) = (state.clone(), query.clone());
+        let message = tokio::task::spawn_blocking(move || {
+            get_live_results(&calculation_state, &calculation_query)
+        })
+        .await
+        .expect("Error occurred calculating results");
+        let message = serde_json::to_string(&message).expect("Error occurred serializing results");
+        if socket.send(Message::Text(message)).await.is_err() {
+            break;
+        }
+
+        // wait for results to change, ignoring anything sent by the client
+        loop {
+            tokio::select! {
+                generation = generations.recv() => match generation {
+                    Ok(_) | Err(RecvError::Lagged(_)) => break,
+                    Err(RecvError::Closed) => return,
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+
+    let _ = socket.close().await;
+}
+
+/// Gets the results requested by a live results connection
+fn get_live_results(state: &AppState, query: &LiveResultsQuery) -> LiveResultsMessage {
+    let generation = state.results_cache.generation();
+
+    calculate_live_results(state, query, generation).unwrap_or_else(|err| LiveResultsMessage {
+        generation,
+        season: None,
+        season_competition: None,
+        competition_event: None,
+        error: Some(err.to_string()),
+    })
+}
+
+fn calculate_live_results(
+    state: &AppState,
+    query: &LiveResultsQuery,
+    generation: u64,
+) -> LuaResult<LiveResultsMessage> {
+    let calculator = || ResultsCalculator::new(&state.database, &state.config);
+
+    Ok(LiveResultsMessage {
+        generation,
+        season: query
+            .season_id
+            .filter(|id| state.seasons_service.get(*id).is_some())
+            .map(|id| {
+                state
+                    .results_cache
+                    .get_season(id, || calculator().calculate_season(id))
+            })
+            .transpose()?,
+        season_competition: query
+            .season_competition_id
+            .filter(|id| state.season_competitions_service.get(*id).is_some())
+            .map(|id| {
+                state
+                    .results_cache
+                    .get_season_competition(id, || calculator().calculate_season_competition(id))
+            })
+            .transpose()?,
+        competition_event: query
+            .competition_event_id
+            .filter(|id| state.competition_events_service.get(*id).is_some())
+            .map(|id| {
+                state
+                    .results_cache
+                    .get_competition_event(id, || calculator().calculate_competition_event(id))
+            })
+            .transpose()?,
+        error: None,
+    })
+}
@@ -7,10 +7,12 @@ use axum::{
     routing::{delete, get, post, put},
     Json, Router,
 };
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
 use uuid::Uuid;
 
 use crate::{
+    calculator::{ranking::RankingPolicy, CalculationLevel, ResultsCalculator},
     database::{score_calculators::ScoreCalculator, users::UserPermission},
     helpers::auth_extractor::AuthToken,
     AppState,
@@ -20,9 +22,26 @@ pub fn route() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_score_calculators))
         .route("/", post(create_score_calculator))
+        .route("/builtin", post(import_builtin_score_calculators))
         .route("/:id", get(get_score_calculator))
         .route("/:id", put(update_score_calculator))
         .route("/:id", delete(delete_score_calculator))
+        .route("/:id/test", post(test_score_calculator))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportBuiltinRequest {
+    replace_existing: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TestScoreCalculatorRequest {
+    level: CalculationLevel,
+    input: JsonValue,
+    #[serde(default)]
+    config: JsonValue,
+    #[serde(default)]
+    ranking: RankingPolicy,
 }
 
 pub async fn list_score_calculators(
@@ -64,8 +83,27 @@ pub async fn create_score_calculator(
         return AuthToken::failure_response();
     };
 
+    if let Err(script_error) = state.score_calculators_service.validate(&request) {
+        state.audit_service.log_data(
+            Some(current_user.id),
+            "score_calculator_create",
+            json!({
+                "id": null,
+                "score_calculator": request,
+                "script_error": script_error,
+                "success": false
+            }),
+        );
+
+        return (StatusCode::BAD_REQUEST, Json(script_error)).into_response();
+    }
+
     let result = state.score_calculators_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "score_calculator_create",
@@ -92,8 +130,27 @@ pub async fn update_score_calculator(
         return AuthToken::failure_response();
     };
 
+    if let Err(script_error) = state.score_calculators_service.validate(&request) {
+        state.audit_service.log_data(
+            Some(current_user.id),
+            "score_calculator_update",
+            json!({
+                "id": id,
+                "score_calculator": request,
+                "script_error": script_error,
+                "success": false
+            }),
+        );
+
+        return (StatusCode::BAD_REQUEST, Json(script_error)).into_response();
+    }
+
     let result = state.score_calculators_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "score_calculator_update",
@@ -121,6 +178,10 @@ pub async fn delete_score_calculator(
 
     let result = state.score_calculators_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "score_calculator_delete",
@@ -135,3 +196,63 @@ pub async fn delete_score_calculator(
         Err(err) => err.to_status_code().into_response(),
     }
 }
+
+/// Creates the built-in score calculators, optionally replacing ones with the same name
+pub async fn import_builtin_score_calculators(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<ImportBuiltinRequest>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .score_calculators_service
+        .import_builtin(request.replace_existing);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "score_calculator_import_builtin",
+        json!({
+            "ids": result.as_ref().ok(),
+            "replace_existing": request.replace_existing,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(ids) => Json(ids).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+/// Runs a score calculator with sample input, returning the raw output, ranks and any error
+pub async fn test_score_calculator(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<TestScoreCalculatorRequest>,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let Some(score_calculator) = state.score_calculators_service.get(id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let result = ResultsCalculator::new(&state.database, &state.config).test_calculator(
+        &score_calculator,
+        request.level,
+        &request.input,
+        &request.config,
+        request.ranking,
+    );
+
+    Json(result).into_response()
+}
@@ -1,21 +1,34 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
-    database::{scores::Score, users::UserPermission},
+    api::changes::can_view,
+    database::{
+        changes::{Change, ChangeAction},
+        scores::{Score, VerificationState},
+        users::UserPermission,
+    },
     helpers::auth_extractor::AuthToken,
+    services::scores::{
+        ScoreFilter, ScorePagination, ScoreSyncOperation, ScoreSyncResult, ScoreSyncStatus,
+    },
     AppState,
 };
 
+/// Maximum number of operations and changes in a sync request and response
+const MAX_SYNC_OPERATIONS: usize = 1000;
+const MAX_SYNC_CHANGES: i64 = 1000;
+
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_scores))
@@ -23,6 +36,19 @@ pub fn route() -> Router<Arc<AppState>> {
         .route("/:id", get(get_score))
         .route("/:id", put(update_score))
         .route("/:id", delete(delete_score))
+        .route("/sync", post(sync_scores))
+        .route("/import/:competition_event_id", post(import_scores))
+        .route(
+            "/import/:competition_event_id/preview",
+            post(preview_score_import),
+        )
+        .route("/:id/history", get(get_score_history))
+        .route("/:id/verify", post(verify_score))
+        .route("/:id/reject", post(reject_score))
+        .route(
+            "/awaiting_verification/:competition_event_id",
+            get(list_scores_awaiting_verification),
+        )
         .route(
             "/group/:group_participation_id/:competition_event_id",
             get(list_scores_for_group),
@@ -36,6 +62,8 @@ pub fn route() -> Router<Arc<AppState>> {
 pub async fn list_scores(
     State(state): State<Arc<AppState>>,
     token: AuthToken,
+    Query(filter): Query<ScoreFilter>,
+    Query(pagination): Query<ScorePagination>,
 ) -> impl IntoResponse {
     let Some(_current_user) = token.authorize(
         &state,
@@ -44,9 +72,12 @@ pub async fn list_scores(
         return AuthToken::failure_response();
     };
 
-    let result = state.scores_service.list();
+    let result = state.scores_service.list(&filter, &pagination);
 
-    Json(result).into_response()
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
 }
 
 pub async fn get_score(
@@ -69,6 +100,26 @@ pub async fn get_score(
     }
 }
 
+pub async fn get_score_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(
+        &state,
+        UserPermission::SCORE_VIEW | UserPermission::SCORE_ENTRY,
+    ) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.scores_service.history(id);
+
+    match result {
+        Some(result) => Json(result).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
 pub async fn create_score(
     State(state): State<Arc<AppState>>,
     token: AuthToken,
@@ -78,7 +129,26 @@ pub async fn create_score(
         return AuthToken::failure_response();
     };
 
-    let result = state.scores_service.create(&request);
+    if let Err(validation_error) = state.scores_service.validate(&request) {
+        state.audit_service.log_data(
+            Some(current_user.id),
+            "score_create",
+            json!({
+                "id": null,
+                "score": request,
+                "validation_error": validation_error,
+                "success": false
+            }),
+        );
+
+        return (StatusCode::BAD_REQUEST, Json(validation_error)).into_response();
+    }
+
+    let result = state.scores_service.create(&request, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
 
     state.audit_service.log_data(
         Some(current_user.id),
@@ -100,13 +170,38 @@ pub async fn update_score(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
     token: AuthToken,
-    Json(request): Json<Score>,
+    Json(mut request): Json<Score>,
 ) -> impl IntoResponse {
     let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
         return AuthToken::failure_response();
     };
 
-    let result = state.scores_service.update(&request);
+    // the score being updated is the one in the path, so a different id in the body is a mistake
+    if request.id.is_some_and(|request_id| request_id != id) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    request.id = Some(id);
+
+    if let Err(validation_error) = state.scores_service.validate(&request) {
+        state.audit_service.log_data(
+            Some(current_user.id),
+            "score_update",
+            json!({
+                "id": id,
+                "score": request,
+                "validation_error": validation_error,
+                "success": false
+            }),
+        );
+
+        return (StatusCode::BAD_REQUEST, Json(validation_error)).into_response();
+    }
+
+    let result = state.scores_service.update(id, &request, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
 
     state.audit_service.log_data(
         Some(current_user.id),
@@ -133,7 +228,11 @@ pub async fn delete_score(
         return AuthToken::failure_response();
     };
 
-    let result = state.scores_service.delete(id);
+    let result = state.scores_service.delete(id, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
 
     state.audit_service.log_data(
         Some(current_user.id),
@@ -150,6 +249,84 @@ pub async fn delete_score(
     }
 }
 
+pub async fn verify_score(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_score_verification_state(
+        state,
+        id,
+        token,
+        VerificationState::Verified,
+        "score_verify",
+    )
+}
+
+pub async fn reject_score(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_score_verification_state(
+        state,
+        id,
+        token,
+        VerificationState::Rejected,
+        "score_reject",
+    )
+}
+
+fn set_score_verification_state(
+    state: Arc<AppState>,
+    id: Uuid,
+    token: AuthToken,
+    verification_state: VerificationState,
+    action: &str,
+) -> Response {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_VERIFY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .scores_service
+        .set_verification_state(id, verification_state, &current_user);
+
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        action,
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+pub async fn list_scores_awaiting_verification(
+    State(state): State<Arc<AppState>>,
+    Path(competition_event_id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::SCORE_VERIFY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .scores_service
+        .list_awaiting_verification(competition_event_id);
+
+    Json(result).into_response()
+}
+
 pub async fn list_scores_for_group(
     State(state): State<Arc<AppState>>,
     Path((group_participation_id, competition_event_id)): Path<(Uuid, Uuid)>,
@@ -187,3 +364,155 @@ pub async fn list_scores_for_team(
 
     Json(result).into_response()
 }
+
+#[derive(Deserialize)]
+pub struct ScoreSyncRequest {
+    operations: Vec<ScoreSyncOperation>,
+    /// Sync token returned by the last sync, which is a change feed sequence number
+    #[serde(default)]
+    sync_token: i64,
+}
+
+#[derive(Serialize)]
+pub struct ScoreSyncResponse {
+    results: Vec<ScoreSyncResult>,
+    /// Changes by other users since the sync token
+    changes: Vec<Change>,
+    sync_token: i64,
+    /// Whether there are more changes, which can be fetched by syncing again with the new sync token
+    more_changes: bool,
+}
+
+/// Applies score operations queued by an offline client and returns changes by other users since its last sync
+pub async fn sync_scores(
+    State(state): State<Arc<AppState>>,
+    token: AuthToken,
+    Json(request): Json<ScoreSyncRequest>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    if request.operations.len() > MAX_SYNC_OPERATIONS {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let results = state
+        .scores_service
+        .sync(&request.operations, &current_user);
+
+    if results
+        .iter()
+        .any(|result| matches!(result.status, ScoreSyncStatus::Success))
+    {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "score_sync",
+        json!({
+            "operations": request.operations,
+            "results": results,
+        }),
+    );
+
+    let changes = state
+        .changes_service
+        .list_after(request.sync_token, MAX_SYNC_CHANGES);
+    let more_changes = changes.len() as i64 == MAX_SYNC_CHANGES;
+    let sync_token = changes
+        .last()
+        .map(|change| change.sequence)
+        .unwrap_or(request.sync_token);
+
+    // the client already has its own score changes
+    let changes = changes
+        .into_iter()
+        .filter(|change| can_view(current_user.permissions, &change.entity))
+        .filter(|change| {
+            change.entity != "score"
+                || matches!(change.action, ChangeAction::Delete)
+                || change
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("modified_by"))
+                    .and_then(|modified_by| modified_by.as_str())
+                    != Some(current_user.id.to_string().as_str())
+        })
+        .collect();
+
+    Json(ScoreSyncResponse {
+        results,
+        changes,
+        sync_token,
+        more_changes,
+    })
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ScoreImportRequest {
+    /// CSV file with a team or group name column and a column for each score field
+    csv: String,
+}
+
+/// Previews importing scores from a CSV file without creating them
+pub async fn preview_score_import(
+    State(state): State<Arc<AppState>>,
+    Path(competition_event_id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<ScoreImportRequest>,
+) -> impl IntoResponse {
+    let Some(_current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state
+        .score_import_service
+        .preview(competition_event_id, &request.csv);
+
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
+
+/// Imports scores from a CSV file, creating all of them or none of them
+pub async fn import_scores(
+    State(state): State<Arc<AppState>>,
+    Path(competition_event_id): Path<Uuid>,
+    token: AuthToken,
+    Json(request): Json<ScoreImportRequest>,
+) -> impl IntoResponse {
+    let Some(current_user) = token.authorize(&state, UserPermission::SCORE_ENTRY) else {
+        return AuthToken::failure_response();
+    };
+
+    let result =
+        state
+            .score_import_service
+            .commit(competition_event_id, &request.csv, &current_user);
+
+    let success = result.as_ref().is_ok_and(|import| import.created.is_some());
+    if success {
+        state.results_cache.invalidate();
+    }
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        "score_import",
+        json!({
+            "competition_event_id": competition_event_id,
+            "csv": request.csv,
+            "created": result.as_ref().ok().and_then(|import| import.created.as_ref()),
+            "success": success
+        }),
+    );
+
+    match result {
+        Ok(result) if success => Json(result).into_response(),
+        Ok(result) => (StatusCode::BAD_REQUEST, Json(result)).into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -23,6 +23,8 @@ pub fn route() -> Router<Arc<AppState>> {
         .route("/:id", get(get_season))
         .route("/:id", put(update_season))
         .route("/:id", delete(delete_season))
+        .route("/:id/lock", post(lock_season))
+        .route("/:id/unlock", post(unlock_season))
 }
 
 pub async fn list_seasons(
@@ -66,6 +68,10 @@ pub async fn create_season(
 
     let result = state.seasons_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_create",
@@ -94,6 +100,10 @@ pub async fn update_season(
 
     let result = state.seasons_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_update",
@@ -121,6 +131,10 @@ pub async fn delete_season(
 
     let result = state.seasons_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_delete",
@@ -135,3 +149,45 @@ pub async fn delete_season(
         Err(err) => err.to_status_code().into_response(),
     }
 }
+
+pub async fn lock_season(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_season_locked(state, id, token, true)
+}
+
+pub async fn unlock_season(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    token: AuthToken,
+) -> impl IntoResponse {
+    set_season_locked(state, id, token, false)
+}
+
+fn set_season_locked(state: Arc<AppState>, id: Uuid, token: AuthToken, locked: bool) -> Response {
+    let Some(current_user) = token.authorize(&state, UserPermission::SETUP_ADMIN) else {
+        return AuthToken::failure_response();
+    };
+
+    let result = state.seasons_service.set_locked(id, locked);
+
+    state.audit_service.log_data(
+        Some(current_user.id),
+        if locked {
+            "season_lock"
+        } else {
+            "season_unlock"
+        },
+        json!({
+            "id": id,
+            "success": result.is_ok()
+        }),
+    );
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => err.to_status_code().into_response(),
+    }
+}
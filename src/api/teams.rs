@@ -63,6 +63,10 @@ pub async fn create_team(
 
     let result = state.teams_service.create(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_create",
@@ -91,6 +95,10 @@ pub async fn update_team(
 
     let result = state.teams_service.update(&request);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_update",
@@ -118,6 +126,10 @@ pub async fn delete_team(
 
     let result = state.teams_service.delete(id);
 
+    if result.is_ok() {
+        state.results_cache.invalidate();
+    }
+
     state.audit_service.log_data(
         Some(current_user.id),
         "season_delete",
@@ -0,0 +1,77 @@
+use serde_json::json;
+
+use crate::{
+    database::score_calculators::{ScoreCalculator, ScoreFieldType},
+    helpers::json::encode_json_string,
+};
+
+/// Gets the standard score calculators that are included with the application
+pub fn builtin_calculators() -> Vec<ScoreCalculator> {
+    Vec::from([
+        ScoreCalculator {
+            id: None,
+            name: String::from("Sum of Points"),
+            description: String::from("Event score is the points of the newest score minus point deductions (none if disqualified), competition and season scores are the sums of the event and competition scores."),
+            script: String::from(include_str!("builtin/sum_of_points.lua")),
+            default_config: encode_json_string(&json!({"weight": 1})),
+            supports_seasons: true,
+            supports_competitions: true,
+            supports_events: true,
+            score_fields: Some(encode_json_string(
+                &json!({"Points": ScoreFieldType::Number}),
+            )),
+        },
+        ScoreCalculator {
+            id: None,
+            name: String::from("Lowest Time Wins"),
+            description: String::from("Event score is the time of the newest score in seconds plus time additions (none if disqualified). Set the ranking direction to lowest wins so the lowest time is ranked first."),
+            script: String::from(include_str!("builtin/lowest_time.lua")),
+            default_config: encode_json_string(&json!({"max_time": null})),
+            supports_seasons: false,
+            supports_competitions: false,
+            supports_events: true,
+            score_fields: Some(encode_json_string(&json!({"Time": ScoreFieldType::Time}))),
+        },
+        ScoreCalculator {
+            id: None,
+            name: String::from("Best of N Attempts"),
+            description: String::from("Each score is an attempt, event score is the highest points of the first N attempts minus point deductions (none if disqualified)."),
+            script: String::from(include_str!("builtin/best_of_attempts.lua")),
+            default_config: encode_json_string(&json!({"attempts": 3})),
+            supports_seasons: false,
+            supports_competitions: false,
+            supports_events: true,
+            score_fields: Some(encode_json_string(
+                &json!({"Points": ScoreFieldType::Number}),
+            )),
+        },
+        ScoreCalculator {
+            id: None,
+            name: String::from("Placement Points"),
+            description: String::from("Teams/groups are placed by the points of their newest score minus point deductions and awarded points for their placement (none if disqualified)."),
+            script: String::from(include_str!("builtin/placement_points.lua")),
+            default_config: encode_json_string(
+                &json!({"points": [10, 8, 6, 5, 4, 3, 2, 1], "default_points": 0}),
+            ),
+            supports_seasons: false,
+            supports_competitions: false,
+            supports_events: true,
+            score_fields: Some(encode_json_string(
+                &json!({"Points": ScoreFieldType::Number}),
+            )),
+        },
+        ScoreCalculator {
+            id: None,
+            name: String::from("Judges Average (Drop High/Low)"),
+            description: String::from("Each judge's newest score is their points, event score is the average after dropping the highest and lowest judges' points, minus point deductions (none if disqualified)."),
+            script: String::from(include_str!("builtin/judges_average.lua")),
+            default_config: encode_json_string(&json!({"drop_highest": 1, "drop_lowest": 1})),
+            supports_seasons: false,
+            supports_competitions: false,
+            supports_events: true,
+            score_fields: Some(encode_json_string(
+                &json!({"Points": ScoreFieldType::Number}),
+            )),
+        },
+    ])
+}
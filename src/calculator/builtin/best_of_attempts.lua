@@ -0,0 +1,56 @@
+-- Best of N Attempts
+-- Each score is an attempt, the event score is the highest "Points" field of the first N attempts, minus any point deduction penalties.
+-- Teams/groups with a disqualification penalty have no event score.
+-- Config:
+--   attempts: number of attempts counted for each team/group
+
+local function to_decimal(value)
+  if value == nil or value == "" then
+    return nil
+  end
+  return decimal(value)
+end
+
+-- Applies point deductions, returning nil if disqualified
+local function apply_penalties(points, penalties)
+  for _, penalty in ipairs(penalties or {}) do
+    if penalty.type == "Disqualification" then
+      return nil
+    elseif penalty.type == "PointDeduction" and points ~= nil then
+      points = points - penalty.value
+    end
+  end
+  return points
+end
+
+function calculate_event_scores(teams, config)
+  local attempts = config.attempts or 3
+  local results = {}
+  for _, team in ipairs(teams) do
+    -- scores are sorted newest to oldest, so the first attempts are at the end
+    local count = 0
+    for index in pairs(team.scores) do
+      if index > count then
+        count = index
+      end
+    end
+
+    local best = nil
+    for index = count, math.max(count - attempts + 1, 1), -1 do
+      local score = team.scores[index]
+      local points = score and to_decimal(score.Points)
+      if points ~= nil and (best == nil or points > best) then
+        best = points
+      end
+    end
+
+    best = apply_penalties(best, team.penalties)
+
+    results[#results + 1] = {
+      id = team.id,
+      event_score = best,
+      competition_data = best or decimal(0),
+    }
+  end
+  return results
+end
@@ -0,0 +1,71 @@
+-- Judges Average (Drop High/Low)
+-- Each judge's newest score is their "Points" for the team/group, the event score is the average after dropping the highest and lowest judges' points.
+-- Scores are only dropped if there are enough left to average.
+-- Point deduction penalties are subtracted from the average, and teams/groups with a disqualification penalty have no event score.
+-- Config:
+--   drop_highest: number of highest scores to drop
+--   drop_lowest: number of lowest scores to drop
+
+local function to_decimal(value)
+  if value == nil or value == "" then
+    return nil
+  end
+  return decimal(value)
+end
+
+-- Applies point deductions, returning nil if disqualified
+local function apply_penalties(points, penalties)
+  for _, penalty in ipairs(penalties or {}) do
+    if penalty.type == "Disqualification" then
+      return nil
+    elseif penalty.type == "PointDeduction" and points ~= nil then
+      points = points - penalty.value
+    end
+  end
+  return points
+end
+
+function calculate_event_scores(teams, config)
+  local drop_highest = config.drop_highest or 1
+  local drop_lowest = config.drop_lowest or 1
+
+  local results = {}
+  for _, team in ipairs(teams) do
+    local values = {}
+    for _, judge in ipairs(team.judges) do
+      local score = judge.scores[1]
+      local points = score and to_decimal(score.Points)
+      if points ~= nil then
+        values[#values + 1] = points
+      end
+    end
+
+    table.sort(values, function(a, b)
+      return a < b
+    end)
+
+    local average = nil
+    if #values > 0 then
+      local first, last = 1, #values
+      if #values > drop_highest + drop_lowest then
+        first = 1 + drop_lowest
+        last = #values - drop_highest
+      end
+
+      local total = decimal(0)
+      for index = first, last do
+        total = total + values[index]
+      end
+      average = total / (last - first + 1)
+    end
+
+    average = apply_penalties(average, team.penalties)
+
+    results[#results + 1] = {
+      id = team.id,
+      event_score = average,
+      competition_data = average or decimal(0),
+    }
+  end
+  return results
+end
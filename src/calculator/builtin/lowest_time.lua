@@ -0,0 +1,53 @@
+-- Lowest Time Wins
+-- Event score is the "Time" field of the newest score in seconds, plus any time addition penalties.
+-- Teams/groups with a disqualification penalty are treated as not finishing.
+-- The competition event's ranking direction should be set to lowest wins.
+-- Times can be entered as seconds, "m:ss.sss" or "h:mm:ss.sss".
+-- The time is passed to the competition calculator, so summing it gives the total time (also ranked lowest wins).
+-- Config:
+--   max_time: times over this number of seconds are treated as not finishing, and teams that don't finish pass this time to the competition calculator
+
+local function parse_time(value)
+  if value == nil or value == "" then
+    return nil
+  end
+  if type(value) == "number" then
+    return decimal(value)
+  end
+
+  local seconds = decimal(0)
+  for part in string.gmatch(value, "[^:]+") do
+    seconds = seconds * 60 + decimal(part:match("^%s*(.-)%s*$"))
+  end
+  return seconds
+end
+
+-- Applies time additions, returning nil if disqualified
+local function apply_penalties(time, penalties)
+  for _, penalty in ipairs(penalties or {}) do
+    if penalty.type == "Disqualification" then
+      return nil
+    elseif penalty.type == "TimeAddition" and time ~= nil then
+      time = time + penalty.value
+    end
+  end
+  return time
+end
+
+function calculate_event_scores(teams, config)
+  local max_time = config.max_time and decimal(config.max_time)
+  local results = {}
+  for _, team in ipairs(teams) do
+    local score = team.scores[1]
+    local time = apply_penalties(score and parse_time(score.Time), team.penalties)
+    if time ~= nil and max_time ~= nil and time > max_time then
+      time = nil
+    end
+    results[#results + 1] = {
+      id = team.id,
+      event_score = time,
+      competition_data = time or max_time,
+    }
+  end
+  return results
+end
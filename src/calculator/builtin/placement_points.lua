@@ -0,0 +1,67 @@
+-- Placement Points
+-- Teams/groups are placed by the "Points" field of their newest score, minus any point deduction penalties, and awarded points for their placement.
+-- Teams/groups with a disqualification penalty aren't placed and have no event score.
+-- Tied teams/groups get the points for the highest placement they share.
+-- Config:
+--   points: array of points awarded for each placement, starting with first place
+--   default_points: points awarded for placements past the end of the points array
+
+local function to_decimal(value)
+  if value == nil or value == "" then
+    return nil
+  end
+  return decimal(value)
+end
+
+-- Applies point deductions, returning nil if disqualified
+local function apply_penalties(points, penalties)
+  for _, penalty in ipairs(penalties or {}) do
+    if penalty.type == "Disqualification" then
+      return nil
+    elseif penalty.type == "PointDeduction" and points ~= nil then
+      points = points - penalty.value
+    end
+  end
+  return points
+end
+
+function calculate_event_scores(teams, config)
+  local placement_points = config.points or {}
+  local default_points = decimal(config.default_points or 0)
+
+  local results = {}
+  local placed = {}
+  for _, team in ipairs(teams) do
+    local score = team.scores[1]
+    local points = apply_penalties(score and to_decimal(score.Points), team.penalties)
+    if points ~= nil then
+      placed[#placed + 1] = { id = team.id, points = points }
+    else
+      results[#results + 1] = {
+        id = team.id,
+        event_score = nil,
+        competition_data = decimal(0),
+      }
+    end
+  end
+
+  table.sort(placed, function(a, b)
+    return a.points > b.points
+  end)
+
+  for index, entry in ipairs(placed) do
+    local placement = index
+    while placement > 1 and placed[placement - 1].points == entry.points do
+      placement = placement - 1
+    end
+
+    local awarded = placement_points[placement] and decimal(placement_points[placement]) or default_points
+    results[#results + 1] = {
+      id = entry.id,
+      event_score = awarded,
+      competition_data = awarded,
+    }
+  end
+
+  return results
+end
@@ -0,0 +1,73 @@
+-- Sum of Points
+-- Event score is the "Points" field of the newest score, minus any point deduction penalties.
+-- Teams/groups with a disqualification penalty have no event score.
+-- Competition and season scores are the sums of the event and competition scores.
+-- Config:
+--   weight: multiplier applied to the score when it is added to the next level
+
+local function to_decimal(value)
+  if value == nil or value == "" then
+    return nil
+  end
+  return decimal(value)
+end
+
+-- Applies point deductions, returning nil if disqualified
+local function apply_penalties(points, penalties)
+  for _, penalty in ipairs(penalties or {}) do
+    if penalty.type == "Disqualification" then
+      return nil
+    elseif penalty.type == "PointDeduction" and points ~= nil then
+      points = points - penalty.value
+    end
+  end
+  return points
+end
+
+local function sum(values)
+  local total = decimal(0)
+  for _, value in pairs(values) do
+    total = total + value
+  end
+  return total
+end
+
+function calculate_event_scores(teams, config)
+  local weight = decimal(config.weight or 1)
+  local results = {}
+  for _, team in ipairs(teams) do
+    local score = team.scores[1]
+    local points = apply_penalties(score and to_decimal(score.Points), team.penalties)
+    results[#results + 1] = {
+      id = team.id,
+      event_score = points,
+      competition_data = (points or decimal(0)) * weight,
+    }
+  end
+  return results
+end
+
+function calculate_competition_scores(teams, config)
+  local weight = decimal(config.weight or 1)
+  local results = {}
+  for _, team in ipairs(teams) do
+    local total = sum(team.data)
+    results[#results + 1] = {
+      id = team.id,
+      competition_score = total,
+      season_data = total * weight,
+    }
+  end
+  return results
+end
+
+function calculate_season_scores(groups, config)
+  local results = {}
+  for _, group in ipairs(groups) do
+    results[#results + 1] = {
+      id = group.id,
+      season_score = sum(group.data),
+    }
+  end
+  return results
+end
@@ -0,0 +1,257 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use mlua::Result as LuaResult;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::{CompetitionResult, EventResult, SeasonResult};
+
+/// Number of generation changes buffered for each subscriber before older ones are dropped
+const GENERATION_CHANNEL_CAPACITY: usize = 16;
+
+/// Cached results, along with the generation they were calculated in
+#[derive(Default)]
+struct CacheEntries {
+    generation: u64,
+    seasons: HashMap<Uuid, SeasonResult>,
+    season_competitions: HashMap<Uuid, CompetitionResult>,
+    competition_events: HashMap<Uuid, EventResult>,
+}
+
+/// Results that can be cached
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ResultsKey {
+    Season(Uuid),
+    SeasonCompetition(Uuid),
+    CompetitionEvent(Uuid),
+}
+
+/// Cache of calculated results for seasons, season competitions and competition events.
+/// The whole cache is cleared and the generation incremented whenever anything that could affect results changes.
+/// Only one caller calculates results that aren't cached, others requesting the same results wait for them.
+#[derive(Clone)]
+pub struct ResultsCache {
+    entries: Arc<Mutex<CacheEntries>>,
+    /// Lock held while calculating each of the results in the current generation
+    calculations: Arc<Mutex<HashMap<ResultsKey, Arc<Mutex<()>>>>>,
+    generation_sender: broadcast::Sender<u64>,
+}
+
+impl Default for ResultsCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResultsCache {
+    pub fn new() -> Self {
+        let (generation_sender, _) = broadcast::channel(GENERATION_CHANNEL_CAPACITY);
+        Self {
+            entries: Arc::new(Mutex::new(CacheEntries::default())),
+            calculations: Arc::new(Mutex::new(HashMap::new())),
+            generation_sender,
+        }
+    }
+
+    /// Gets the current generation, which changes whenever the cache is invalidated
+    pub fn generation(&self) -> u64 {
+        self.entries.lock().unwrap().generation
+    }
+
+    /// Subscribes to generation changes, receiving the new generation each time the cache is invalidated
+    pub fn subscribe(&self) -> broadcast::Receiver<u64> {
+        self.generation_sender.subscribe()
+    }
+
+    /// Clears all cached results, should be called after any change that could affect results
+    pub fn invalidate(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.seasons.clear();
+        entries.season_competitions.clear();
+        entries.competition_events.clear();
+        // calculations already running are for the previous generation, so don't wait for them
+        self.calculations.lock().unwrap().clear();
+
+        // sending only fails if there are no subscribers
+        let _ = self.generation_sender.send(entries.generation);
+    }
+
+    /// Gets cached results, otherwise calculates and stores them.
+    /// Blocks while another caller is calculating the same results, then uses theirs.
+    fn get_or_calculate<T: Clone>(
+        &self,
+        key: ResultsKey,
+        cached: impl Fn(&CacheEntries) -> Option<&T>,
+        calculate: impl FnOnce() -> LuaResult<T>,
+        store: impl FnOnce(&mut CacheEntries, &T),
+    ) -> LuaResult<T> {
+        if let Some(result) = cached(&self.entries.lock().unwrap()) {
+            return Ok(result.clone());
+        }
+
+        let calculation = self
+            .calculations
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .clone();
+        // a calculation that panicked didn't store anything, so it's fine to calculate again
+        let _calculating = calculation.lock().unwrap_or_else(|err| err.into_inner());
+
+        let generation = {
+            let entries = self.entries.lock().unwrap();
+            if let Some(result) = cached(&entries) {
+                return Ok(result.clone());
+            }
+            entries.generation
+        };
+
+        let result = calculate()?;
+
+        let mut entries = self.entries.lock().unwrap();
+        // results calculated before the cache was invalidated may be out of date
+        if entries.generation == generation {
+            store(&mut entries, &result);
+        }
+
+        Ok(result)
+    }
+
+    /// Gets cached season results, otherwise calculates and caches them along with the results of its competitions and events
+    pub fn get_season(
+        &self,
+        id: Uuid,
+        calculate: impl FnOnce() -> LuaResult<SeasonResult>,
+    ) -> LuaResult<SeasonResult> {
+        self.get_or_calculate(
+            ResultsKey::Season(id),
+            |entries| entries.seasons.get(&id),
+            calculate,
+            |entries, result| {
+                for competition in &result.competitions {
+                    for event in &competition.events {
+                        entries
+                            .competition_events
+                            .insert(event.competition_event_id, event.clone());
+                    }
+                    entries
+                        .season_competitions
+                        .insert(competition.season_competition_id, competition.clone());
+                }
+                entries.seasons.insert(id, result.clone());
+            },
+        )
+    }
+
+    /// Gets cached season competition results, otherwise calculates and caches them along with the results of its events
+    pub fn get_season_competition(
+        &self,
+        id: Uuid,
+        calculate: impl FnOnce() -> LuaResult<CompetitionResult>,
+    ) -> LuaResult<CompetitionResult> {
+        self.get_or_calculate(
+            ResultsKey::SeasonCompetition(id),
+            |entries| entries.season_competitions.get(&id),
+            calculate,
+            |entries, result| {
+                for event in &result.events {
+                    entries
+                        .competition_events
+                        .insert(event.competition_event_id, event.clone());
+                }
+                entries.season_competitions.insert(id, result.clone());
+            },
+        )
+    }
+
+    /// Gets cached competition event results, otherwise calculates and caches them
+    pub fn get_competition_event(
+        &self,
+        id: Uuid,
+        calculate: impl FnOnce() -> LuaResult<EventResult>,
+    ) -> LuaResult<EventResult> {
+        self.get_or_calculate(
+            ResultsKey::CompetitionEvent(id),
+            |entries| entries.competition_events.get(&id),
+            calculate,
+            |entries, result| {
+                entries.competition_events.insert(id, result.clone());
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+
+    fn event_result(id: Uuid) -> EventResult {
+        EventResult {
+            competition_event_id: id,
+            event_name: String::from("Event"),
+            results: Vec::new(),
+            under_protest: false,
+            penalties: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn results_are_calculated_once_for_concurrent_requests() {
+        let cache = ResultsCache::new();
+        let calculations = Arc::new(AtomicUsize::new(0));
+        let id = Uuid::new_v4();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let calculations = calculations.clone();
+                thread::spawn(move || {
+                    cache
+                        .get_competition_event(id, || {
+                            calculations.fetch_add(1, Ordering::SeqCst);
+                            thread::sleep(Duration::from_millis(50));
+                            Ok(event_result(id))
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap().competition_event_id, id);
+        }
+        assert_eq!(calculations.load(Ordering::SeqCst), 1);
+
+        cache.invalidate();
+        cache
+            .get_competition_event(id, || {
+                calculations.fetch_add(1, Ordering::SeqCst);
+                Ok(event_result(id))
+            })
+            .unwrap();
+        assert_eq!(calculations.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_calculations_are_not_cached() {
+        let cache = ResultsCache::new();
+        let id = Uuid::new_v4();
+
+        assert!(cache
+            .get_competition_event(id, || Err(mlua::Error::runtime("failed")))
+            .is_err());
+        assert!(cache
+            .get_competition_event(id, || Ok(event_result(id)))
+            .is_ok());
+    }
+}
@@ -1,7 +1,12 @@
-use mlua::{Error, FromLua, Lua, MetaMethod, Result, UserData, UserDataMethods, Value};
-use num_traits::{pow::Pow, FromPrimitive};
-use rust_decimal::Decimal;
+use mlua::{
+    Error, FromLua, Lua, MetaMethod, Result, Table, UserData, UserDataMethods, Value, Variadic,
+};
+use num_traits::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, MathematicalOps, RoundingStrategy};
 
+/// Exact decimal number for use in score calculators.
+/// Arithmetic and comparisons work with a mix of decimals, Lua numbers and numeric strings.
+/// Note that Lua only uses `==` on two userdata values, so `decimal(1) == 1` is always false.
 #[derive(Copy, Clone)]
 pub struct DecimalValue {
     value: Decimal,
@@ -11,42 +16,136 @@ impl DecimalValue {
         Self { value }
     }
 
+    pub fn value(&self) -> Decimal {
+        self.value
+    }
+
+    /// Converts a decimal, Lua number or numeric string to a decimal
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let conversion_error = |message: &str| Error::FromLuaConversionError {
+            from: value.type_name(),
+            to: "decimal",
+            message: Some(String::from(message)),
+        };
+
+        match value {
+            Value::Integer(value) => Ok(Self::new(Decimal::from(*value))),
+            Value::Number(value) => Decimal::from_f64(*value)
+                .map(Self::new)
+                .ok_or_else(|| conversion_error("number is out of range")),
+            Value::String(value) => {
+                let value = value.to_str()?.trim();
+                Decimal::from_str_exact(value)
+                    .or_else(|_| Decimal::from_scientific(value))
+                    .map(Self::new)
+                    .map_err(|_| conversion_error("string is not a valid number"))
+            }
+            Value::UserData(value) => value
+                .borrow::<Self>()
+                .map(|value| *value)
+                .map_err(|_| conversion_error("userdata is not a decimal")),
+            _ => Err(conversion_error("expected a decimal, number or string")),
+        }
+    }
+
+    /// Converts the decimal to a Lua integer if it is a whole number that fits, otherwise to a Lua float
+    pub fn to_number(&self) -> Value<'static> {
+        if self.value.fract().is_zero() {
+            if let Some(value) = self.value.to_i64() {
+                return Value::Integer(value);
+            }
+        }
+        Value::Number(self.value.to_f64().unwrap_or(f64::NAN))
+    }
+
+    /// Creates the global `decimal` table.
+    /// Calling it converts a value to a decimal, and it has the `sum`, `min` and `max` helper functions.
     pub fn add_constructor(lua: &Lua, function_name: &str) {
         let globals = lua.globals();
-        let decimal_constructor = lua
-            .create_function(|_, value: Value| match value {
-                Value::Nil => Err(Error::UserDataTypeMismatch),
-                Value::Boolean(_) => Err(Error::UserDataTypeMismatch),
-                Value::LightUserData(_) => Err(Error::UserDataTypeMismatch),
-                Value::Integer(value) => Decimal::from_i64(value)
-                    .map(DecimalValue::new)
-                    .ok_or(Error::UserDataTypeMismatch),
-                Value::Number(value) => Decimal::from_f64(value)
-                    .map(DecimalValue::new)
-                    .ok_or(Error::UserDataTypeMismatch),
-                Value::String(value) => Decimal::from_str_radix(value.to_str().unwrap(), 10)
-                    .map(DecimalValue::new)
-                    .or(Err(Error::UserDataTypeMismatch)),
-                Value::Table(_) => Err(Error::UserDataTypeMismatch),
-                Value::Function(_) => Err(Error::UserDataTypeMismatch),
-                Value::Thread(_) => Err(Error::UserDataTypeMismatch),
-                Value::UserData(value) => value
-                    .borrow::<Self>()
-                    .map(|value| *value)
-                    .or(Err(Error::UserDataTypeMismatch)),
-                Value::Error(_) => Err(Error::UserDataTypeMismatch),
-            })
+
+        let decimal_table = lua.create_table().unwrap();
+        decimal_table
+            .set(
+                "sum",
+                lua.create_function(|_, list: Table| {
+                    list.sequence_values::<Value>().try_fold(
+                        Self::new(Decimal::ZERO),
+                        |total, value| {
+                            let value = Self::from_value(&value?)?;
+                            checked(total.value.checked_add(value.value), "addition")
+                        },
+                    )
+                })
+                .unwrap(),
+            )
+            .unwrap();
+        decimal_table
+            .set(
+                "min",
+                lua.create_function(|_, values: Variadic<DecimalValue>| {
+                    values
+                        .into_iter()
+                        .min_by_key(|value| value.value)
+                        .ok_or_else(|| Error::runtime("decimal.min requires at least one value"))
+                })
+                .unwrap(),
+            )
+            .unwrap();
+        decimal_table
+            .set(
+                "max",
+                lua.create_function(|_, values: Variadic<DecimalValue>| {
+                    values
+                        .into_iter()
+                        .max_by_key(|value| value.value)
+                        .ok_or_else(|| Error::runtime("decimal.max requires at least one value"))
+                })
+                .unwrap(),
+            )
             .unwrap();
-        globals.set(function_name, decimal_constructor).unwrap();
+
+        let metatable = lua.create_table().unwrap();
+        metatable
+            .set(
+                "__call",
+                lua.create_function(|_, (_, value): (Table, Value)| Self::from_value(&value))
+                    .unwrap(),
+            )
+            .unwrap();
+        // the metatable is shared by each script's copy of the constructor, so scripts can't get it
+        metatable.set("__metatable", false).unwrap();
+        decimal_table.set_metatable(Some(metatable));
+
+        globals.set(function_name, decimal_table).unwrap();
     }
 }
 
+/// Converts the result of a checked operation, raising a Lua error if it failed
+fn checked(result: Option<Decimal>, operation: &str) -> Result<DecimalValue> {
+    result
+        .map(DecimalValue::new)
+        .ok_or_else(|| Error::runtime(format!("decimal {} overflowed or was undefined", operation)))
+}
+
+/// Raises a decimal to a power, raising a Lua error if the result isn't a real number
+fn pow(base: Decimal, exponent: Decimal) -> Result<DecimalValue> {
+    if base.is_sign_negative() && !base.is_zero() && !exponent.fract().is_zero() {
+        return Err(Error::runtime(
+            "decimal exponentiation of a negative number by a non-integer is undefined",
+        ));
+    }
+    if base.is_zero() && exponent.is_sign_negative() && !exponent.is_zero() {
+        return Err(Error::runtime(
+            "decimal exponentiation of zero by a negative number is undefined",
+        ));
+    }
+
+    checked(base.checked_powd(exponent), "exponentiation")
+}
+
 impl<'lua> FromLua<'lua> for DecimalValue {
     fn from_lua(value: Value<'lua>, _: &'lua Lua) -> Result<Self> {
-        match value {
-            Value::UserData(userdata) => Ok(*userdata.borrow::<Self>()?),
-            _ => unreachable!(),
-        }
+        Self::from_value(&value)
     }
 }
 
@@ -54,27 +153,37 @@ impl UserData for DecimalValue {
     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
         methods.add_meta_function(
             MetaMethod::Add,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value + b.value)),
+            |_, (a, b): (DecimalValue, DecimalValue)| {
+                checked(a.value.checked_add(b.value), "addition")
+            },
         );
         methods.add_meta_function(
             MetaMethod::Sub,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value - b.value)),
+            |_, (a, b): (DecimalValue, DecimalValue)| {
+                checked(a.value.checked_sub(b.value), "subtraction")
+            },
         );
         methods.add_meta_function(
             MetaMethod::Mul,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value * b.value)),
+            |_, (a, b): (DecimalValue, DecimalValue)| {
+                checked(a.value.checked_mul(b.value), "multiplication")
+            },
         );
         methods.add_meta_function(
             MetaMethod::Div,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value / b.value)),
+            |_, (a, b): (DecimalValue, DecimalValue)| {
+                checked(a.value.checked_div(b.value), "division")
+            },
         );
         methods.add_meta_function(
             MetaMethod::Mod,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value % b.value)),
+            |_, (a, b): (DecimalValue, DecimalValue)| {
+                checked(a.value.checked_rem(b.value), "modulo")
+            },
         );
         methods.add_meta_function(
             MetaMethod::Pow,
-            |_, (a, b): (DecimalValue, DecimalValue)| Ok(DecimalValue::new(a.value.pow(b.value))),
+            |_, (a, b): (DecimalValue, DecimalValue)| pow(a.value, b.value),
         );
         methods.add_meta_function(MetaMethod::Unm, |_, a: DecimalValue| {
             Ok(DecimalValue::new(-a.value))
@@ -82,7 +191,10 @@ impl UserData for DecimalValue {
         methods.add_meta_function(
             MetaMethod::IDiv,
             |_, (a, b): (DecimalValue, DecimalValue)| {
-                Ok(DecimalValue::new((a.value / b.value).floor()))
+                checked(
+                    a.value.checked_div(b.value).map(|value| value.floor()),
+                    "division",
+                )
             },
         );
         methods.add_meta_function(MetaMethod::Eq, |_, (a, b): (DecimalValue, DecimalValue)| {
@@ -94,7 +206,137 @@ impl UserData for DecimalValue {
         methods.add_meta_function(MetaMethod::Le, |_, (a, b): (DecimalValue, DecimalValue)| {
             Ok(a.value <= b.value)
         });
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+            Ok(this.value.to_string())
+        });
+        methods.add_meta_function(MetaMethod::Concat, |lua, (a, b): (Value, Value)| {
+            let to_string = |value: Value| -> Result<String> {
+                if let Value::UserData(userdata) = &value {
+                    if let Ok(value) = userdata.borrow::<DecimalValue>() {
+                        return Ok(value.value.to_string());
+                    }
+                }
+                let type_name = value.type_name();
+                lua.coerce_string(value)?
+                    .map(|value| value.to_string_lossy().into_owned())
+                    .ok_or_else(|| {
+                        Error::runtime(format!("attempt to concatenate a {} value", type_name))
+                    })
+            };
+            Ok(to_string(a)? + &to_string(b)?)
+        });
+
+        // rounds half away from zero to the given number of decimal places (default 0)
+        methods.add_method("round", |_, this, decimal_places: Option<u32>| {
+            Ok(DecimalValue::new(this.value.round_dp_with_strategy(
+                decimal_places.unwrap_or(0),
+                RoundingStrategy::MidpointAwayFromZero,
+            )))
+        });
+        methods.add_method("floor", |_, this, ()| {
+            Ok(DecimalValue::new(this.value.floor()))
+        });
+        methods.add_method("ceil", |_, this, ()| {
+            Ok(DecimalValue::new(this.value.ceil()))
+        });
+        methods.add_method("abs", |_, this, ()| Ok(DecimalValue::new(this.value.abs())));
+        methods.add_method("min", |_, this, other: DecimalValue| {
+            Ok(DecimalValue::new(this.value.min(other.value)))
+        });
+        methods.add_method("max", |_, this, other: DecimalValue| {
+            Ok(DecimalValue::new(this.value.max(other.value)))
+        });
+        methods.add_method("sqrt", |_, this, ()| {
+            checked(this.value.sqrt(), "square root")
+        });
+        methods.add_method("tostring", |_, this, ()| Ok(this.value.to_string()));
+        methods.add_method("tonumber", |_, this, ()| Ok(this.to_number()));
     }
 
     fn add_fields<'lua, F: mlua::prelude::LuaUserDataFields<'lua, Self>>(_fields: &mut F) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expression: &str) -> Result<String> {
+        let lua = Lua::new();
+        DecimalValue::add_constructor(&lua, "decimal");
+        lua.load(format!("return tostring({})", expression)).eval()
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        assert_eq!(eval("decimal(0.1) + decimal(0.2)").unwrap(), "0.3");
+        assert_eq!(eval("decimal(\"1.10\") - 1").unwrap(), "0.10");
+        assert_eq!(eval("decimal(3) * \"2.5\"").unwrap(), "7.5");
+        assert_eq!(eval("decimal(1) / 4").unwrap(), "0.25");
+        assert_eq!(eval("decimal(7) % 3").unwrap(), "1");
+        assert_eq!(eval("decimal(-7) // 2").unwrap(), "-4");
+        assert_eq!(eval("-decimal(2)").unwrap(), "-2");
+    }
+
+    #[test]
+    fn invalid_arithmetic_raises_errors() {
+        assert!(eval("decimal(1) / 0").is_err());
+        assert!(eval("decimal(\"79228162514264337593543950335\") + 1").is_err());
+        assert!(eval("decimal(\"abc\")").is_err());
+        assert!(eval("decimal({})").is_err());
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(eval("decimal(2) ^ 10 == decimal(1024)").unwrap(), "true");
+        assert_eq!(eval("decimal(-2) ^ 3 == decimal(-8)").unwrap(), "true");
+        assert_eq!(eval("decimal(2) ^ -1 == decimal(\"0.5\")").unwrap(), "true");
+        assert_eq!(
+            eval("(decimal(4) ^ 0.5):round(10) == decimal(2)").unwrap(),
+            "true"
+        );
+        assert_eq!(eval("decimal(0) ^ 0 == decimal(1)").unwrap(), "true");
+        assert_eq!(eval("decimal(0) ^ 2 == decimal(0)").unwrap(), "true");
+    }
+
+    #[test]
+    fn pow_of_negative_base_by_non_integer_raises_error() {
+        assert!(eval("decimal(-2) ^ 0.5").is_err());
+        assert!(eval("decimal(-1) ^ \"1.5\"").is_err());
+    }
+
+    #[test]
+    fn pow_of_zero_by_negative_exponent_raises_error() {
+        assert!(eval("decimal(0) ^ -1").is_err());
+        assert!(eval("decimal(0) ^ -0.5").is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(eval("decimal(1) < 2").unwrap(), "true");
+        assert_eq!(eval("decimal(\"2.0\") <= decimal(2)").unwrap(), "true");
+        assert_eq!(eval("decimal(\"2.0\") == decimal(2)").unwrap(), "true");
+        assert_eq!(eval("decimal(1) == 1").unwrap(), "false");
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(eval("decimal(\"2.5\"):round()").unwrap(), "3");
+        assert_eq!(eval("decimal(\"-2.5\"):round()").unwrap(), "-3");
+        assert_eq!(eval("decimal(\"1.2345\"):round(2)").unwrap(), "1.23");
+        assert_eq!(eval("decimal(\"1.5\"):floor()").unwrap(), "1");
+        assert_eq!(eval("decimal(\"1.5\"):ceil()").unwrap(), "2");
+    }
+
+    #[test]
+    fn helpers() {
+        assert_eq!(
+            eval("decimal.sum({1, \"2.5\", decimal(3)})").unwrap(),
+            "6.5"
+        );
+        assert_eq!(eval("decimal.min(3, 1, 2)").unwrap(), "1");
+        assert_eq!(eval("decimal.max(3, 1, 2)").unwrap(), "3");
+        assert!(eval("decimal.min()").is_err());
+        assert_eq!(eval("decimal(\"1.5\") .. \"s\"").unwrap(), "1.5s");
+        assert_eq!(eval("math.type(decimal(2):tonumber())").unwrap(), "integer");
+    }
+}
@@ -1,57 +1,230 @@
+pub mod builtin;
+pub mod cache;
 pub mod decimal;
+pub mod ranking;
+pub mod sandbox;
+pub mod validation;
 
-use mlua::{Lua, Value as LuaValue};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    ffi::c_void,
+};
+
+use mlua::{
+    Error as LuaError, Function, Lua, LuaSerdeExt, Result as LuaResult, SerializeOptions, Table,
+    Value as LuaValue,
+};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::{
-    database::{score_calculators::ScoreCalculator, Database},
+    config::AppConfig,
+    database::{
+        competition_events::CompetitionEvent,
+        penalties::{Penalty, PenaltyCode, PenaltyType},
+        score_calculators::ScoreCalculator,
+        scores::{Score, ScoreType, VerificationState},
+        season_competitions::SeasonCompetition,
+        Database,
+    },
+    helpers::json::decode_json_string,
     services::{
-        competition_events::CompetitionEventsService,
-        group_participation::GroupParticipationsService, groups::GroupsService,
-        score_calculators::ScoreCalculatorsService, season_competitions::SeasonCompetitionsService,
+        competition_events::CompetitionEventsService, competitions::CompetitionsService,
+        events::EventsService, group_participation::GroupParticipationsService,
+        groups::GroupsService, penalties::PenaltiesService, penalty_codes::PenaltyCodesService,
+        protests::ProtestsService, score_calculators::ScoreCalculatorsService,
+        scores::ScoresService, season_competitions::SeasonCompetitionsService,
         seasons::SeasonsService, teams::TeamsService,
     },
 };
 
-use self::decimal::DecimalValue;
+use self::{
+    decimal::DecimalValue,
+    ranking::{RankKey, RankingPolicy},
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub enum ScoreResult {
     Group {
         group_participation_id: Uuid,
         group_name: String,
+        score: Option<Decimal>,
         rank: Option<Decimal>,
     },
     Team {
         team_id: Uuid,
         group_name: String,
         team_name: String,
+        score: Option<Decimal>,
         rank: Option<Decimal>,
     },
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct EventResult {
+    pub competition_event_id: Uuid,
     pub event_name: String,
     pub results: Vec<ScoreResult>,
+    /// Whether there are open protests against the results or any scores of the competition event
+    pub under_protest: bool,
+    pub penalties: Vec<PenaltyResult>,
+}
+
+/// Penalty given to a team/group in a competition event, with its reason code
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PenaltyResult {
+    pub penalty_id: Uuid,
+    pub score_type: ScoreType,
+    pub subject_id: Uuid,
+    pub code: String,
+    pub description: String,
+    pub penalty_type: PenaltyType,
+    pub value: Option<Decimal>,
+    pub notes: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CompetitionResult {
+    pub season_competition_id: Uuid,
     pub competition_name: String,
     pub results: Vec<ScoreResult>,
+    /// Whether any of the competition's events are under protest
+    pub under_protest: bool,
     pub events: Vec<EventResult>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SeasonResult {
+    pub season_id: Uuid,
     pub season_name: String,
     pub results: Vec<ScoreResult>,
+    /// Whether any of the season's competitions are under protest
+    pub under_protest: bool,
     pub competitions: Vec<CompetitionResult>,
 }
 
+/// Team or group participating in a season
+#[derive(Clone)]
+struct Participant {
+    score_type: ScoreType,
+    id: Uuid,
+    group_participation_id: Uuid,
+    group_name: String,
+    team_name: String,
+}
+impl Participant {
+    fn type_name(&self) -> &'static str {
+        match self.score_type {
+            ScoreType::Group => "Group",
+            ScoreType::Team => "Team",
+        }
+    }
+
+    fn is_type(&self, score_type: &ScoreType) -> bool {
+        matches!(
+            (&self.score_type, score_type),
+            (ScoreType::Group, ScoreType::Group) | (ScoreType::Team, ScoreType::Team)
+        )
+    }
+
+    fn to_result(&self, score: Option<Decimal>, rank: Option<Decimal>) -> ScoreResult {
+        match self.score_type {
+            ScoreType::Group => ScoreResult::Group {
+                group_participation_id: self.id,
+                group_name: self.group_name.clone(),
+                score,
+                rank,
+            },
+            ScoreType::Team => ScoreResult::Team {
+                team_id: self.id,
+                group_name: self.group_name.clone(),
+                team_name: self.team_name.clone(),
+                score,
+                rank,
+            },
+        }
+    }
+}
+
+/// Level that a score calculator script is run at
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum CalculationLevel {
+    Event,
+    Competition,
+    Season,
+}
+impl CalculationLevel {
+    fn function_name(&self) -> &'static str {
+        match self {
+            Self::Event => "calculate_event_scores",
+            Self::Competition => "calculate_competition_scores",
+            Self::Season => "calculate_season_scores",
+        }
+    }
+
+    fn score_key(&self) -> &'static str {
+        match self {
+            Self::Event => "event_score",
+            Self::Competition => "competition_score",
+            Self::Season => "season_score",
+        }
+    }
+
+    fn data_key(&self) -> Option<&'static str> {
+        match self {
+            Self::Event => Some("competition_data"),
+            Self::Competition => Some("season_data"),
+            Self::Season => None,
+        }
+    }
+
+    fn is_supported_by(&self, score_calculator: &ScoreCalculator) -> bool {
+        match self {
+            Self::Event => score_calculator.supports_events,
+            Self::Competition => score_calculator.supports_competitions,
+            Self::Season => score_calculator.supports_seasons,
+        }
+    }
+}
+
+/// Score and rank of a single item returned when testing a score calculator
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TestRank {
+    pub id: String,
+    pub score: Option<Decimal>,
+    pub tiebreakers: Vec<Option<Decimal>>,
+    pub rank: Option<Decimal>,
+}
+
+/// Result of running a score calculator function with sample input
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CalculatorTestResult {
+    /// Value returned from the calculator function, with decimals converted to strings
+    pub output: Option<JsonValue>,
+    /// Returned items in ranked order
+    pub ranks: Vec<TestRank>,
+    /// Error message and stack trace if the script failed or returned invalid output
+    pub error: Option<String>,
+}
+
+/// Maximum depth of nested tables converted when displaying calculator output
+const MAX_OUTPUT_DEPTH: usize = 32;
+/// Maximum number of values converted when displaying calculator output
+const MAX_OUTPUT_VALUES: usize = 100_000;
+
+/// Tables already converted and the number of values converted so far when displaying calculator output
+#[derive(Default)]
+struct OutputConversion {
+    visited: HashSet<*const c_void>,
+    values: usize,
+}
+
+/// Score and passed-through data returned by a score calculator for a single team/group
+type CalculatorOutput<'lua> = HashMap<Uuid, (RankKey, LuaValue<'lua>)>;
+
 /// Score results calculator
 ///
 /// Score calculation scripts:
@@ -62,17 +235,36 @@ pub struct SeasonResult {
 ///   {
 ///     "id": <team/group id>,
 ///     "type": <either "Team" or "Group">
-///     "scores": [ // sorted newest to oldest, invalid ones are excluded, nil values for items with disqualified flag set
+///     "scores": [ // sorted newest to oldest, invalid and rejected ones are excluded (as are unverified ones if the competition event only uses verified scores), nil values for items with disqualified flag set
 ///       {
 ///         // score data object
 ///       }
+///     ],
+///     "judges": [ // the same scores grouped by judge, ordered by each judge's newest score
+///       {
+///         "id": <judge user id, nil for scores without a judge>,
+///         "scores": [ // sorted newest to oldest, in the same format as above
+///           {
+///             // score data object
+///           }
+///         ]
+///       }
+///     ],
+///     "penalties": [ // sorted oldest to newest
+///       {
+///         "code": <penalty reason code>,
+///         "type": <one of "PointDeduction", "TimeAddition" or "Disqualification">,
+///         "value": <decimal points deducted or seconds added, nil for disqualifications>,
+///         "notes": <string or nil>
+///       }
 ///     ]
 ///   }
 /// Parameter 2: object of score config options
 /// Return: array of teams/groups, ordering doesn't matter, with each item in the format:
 ///   {
 ///     "id": <team/group id>,
-///     "event_score": <number, will be ranked highest to lowest, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "event_score": <number, will be ranked using the ranking policy, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "tiebreakers": <optional array of numbers compared in order when scores are tied, using the same ranking direction>,
 ///     "competition_data": <anything, will be passed to the competition score calculator>
 ///   }
 ///
@@ -82,6 +274,7 @@ pub struct SeasonResult {
 /// Parameter 1: array of teams/groups with each item in the format:
 ///   {
 ///     "id": <team/group id>,
+///     "type": <either "Team" or "Group">
 ///     "data": [ // array of data passed from each calculator for the events in this competition
 ///       <data from competition event calculators>
 ///     ]
@@ -90,7 +283,8 @@ pub struct SeasonResult {
 /// Return: array of teams/groups, ordering doesn't matter, with each item in the format:
 ///   {
 ///     "id": <team/group id>,
-///     "competition_score": <number, will be ranked highest to lowest, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "competition_score": <number, will be ranked using the ranking policy, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "tiebreakers": <optional array of numbers compared in order when scores are tied, using the same ranking direction>,
 ///     "season_data": <anything, will be passed to the season score calculator>
 ///   }
 ///
@@ -99,7 +293,7 @@ pub struct SeasonResult {
 /// Lua function: `calculate_season_scores`
 /// Parameter 1: array of groups with each item in the format:
 ///   {
-///     "id": <group id>,
+///     "id": <group participation id>,
 ///     "data": [ // array of data passed from each calculator for the competitions in this season (note that this includes all entries for each team that belongs to this group)
 ///       <data from season competition calculators>
 ///     ],
@@ -107,177 +301,870 @@ pub struct SeasonResult {
 /// Parameter 2: object of score config options
 /// Return: array of groups, ordering doesn't matter, with each item in the format:
 ///   {
-///     "id": <group_id>,
-///     "season_score": <number, will be ranked highest to lowest, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "id": <group participation id>,
+///     "season_score": <number, will be ranked using the ranking policy, ties are allowed, nil items will be put at the bottom and flagged>,
+///     "tiebreakers": <optional array of numbers compared in order when scores are tied, using the same ranking direction>,
 ///   }
 ///
+/// Scores may be returned as Lua numbers, numeric strings or `decimal` values.
+///
+/// The `decimal` global provides exact decimal arithmetic:
+///   - `decimal(value)` converts a number, numeric string or decimal to a decimal
+///   - arithmetic and comparison operators work with any mix of decimals, numbers and numeric strings (except `==`, which needs two decimals)
+///   - methods: `round(decimal_places)` (half away from zero), `floor()`, `ceil()`, `abs()`, `min(other)`, `max(other)`, `sqrt()`, `tostring()` and `tonumber()`
+///   - `tostring(value)` and `..` work on decimals
+///   - `decimal.sum(list)`, `decimal.min(...)` and `decimal.max(...)` helpers
+///
+/// The score config options are the calculator's default config overridden by the config set on the season, season competition or competition event.
+/// Teams and groups are ranked separately, using the ranking method and direction set on the season, season competition or competition event.
+/// Disabled season competitions, competition events, events, group participations, groups and teams are left out.
+///
+/// Scripts are run in a sandbox without the io, os, package or debug libraries and with instruction and memory limits (see `sandbox::new`).
+///
 pub struct ResultsCalculator {
     pub lua: Lua,
     score_calculator_service: ScoreCalculatorsService,
     season_service: SeasonsService,
+    competition_service: CompetitionsService,
     season_competition_service: SeasonCompetitionsService,
+    event_service: EventsService,
     competition_event_service: CompetitionEventsService,
     group_service: GroupsService,
     group_participation_service: GroupParticipationsService,
     team_service: TeamsService,
+    score_service: ScoresService,
+    protest_service: ProtestsService,
+    penalty_service: PenaltiesService,
+    penalty_code_service: PenaltyCodesService,
 }
 
 impl ResultsCalculator {
-    pub fn new(database: &Database) -> Self {
-        let lua = Lua::new();
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
+        let lua = sandbox::new(config);
 
         DecimalValue::add_constructor(&lua, "decimal");
 
         Self {
             lua,
-            score_calculator_service: ScoreCalculatorsService::new(database),
+            score_calculator_service: ScoreCalculatorsService::new(database, config),
             season_service: SeasonsService::new(database),
+            competition_service: CompetitionsService::new(database),
             season_competition_service: SeasonCompetitionsService::new(database),
+            event_service: EventsService::new(database),
             competition_event_service: CompetitionEventsService::new(database),
             group_service: GroupsService::new(database),
             group_participation_service: GroupParticipationsService::new(database),
             team_service: TeamsService::new(database),
+            score_service: ScoresService::new(database, config),
+            protest_service: ProtestsService::new(database, config),
+            penalty_service: PenaltiesService::new(database),
+            penalty_code_service: PenaltyCodesService::new(database),
         }
     }
 
-    pub fn calculate_team_event_score(
-        &self,
-        competition_event_id: Uuid,
-        team_id: Uuid,
-    ) -> (Option<Decimal>, LuaValue) {
-        let competition_event = self
-            .competition_event_service
-            .get(competition_event_id)
-            .expect("Error occurred getting competition event");
-        let score_calculator = competition_event
-            .score_calculator
-            .and_then(|score_calculator| self.score_calculator_service.get(score_calculator))
-            .unwrap_or_else(ScoreCalculator::get_default);
-        let team = self
-            .team_service
-            .get(team_id)
-            .expect("Error occurred getting team");
-        let group = self
-            .group_service
-            .get(
-                self.group_participation_service
-                    .get(team.group_participation_id)
-                    .expect("Error occurred getting group participation")
-                    .group_id,
-            )
-            .expect("Error occurred getting group");
-
-        todo!()
-    }
-
-    pub fn calculate_group_event_score(
-        &self,
-        competition_event_id: Uuid,
-        group_participation_id: Uuid,
-    ) -> (Option<Decimal>, LuaValue) {
-        let competition_event = self
-            .competition_event_service
-            .get(competition_event_id)
-            .expect("Error occurred getting competition event");
-        let score_calculator = competition_event
-            .score_calculator
-            .and_then(|score_calculator| self.score_calculator_service.get(score_calculator))
-            .unwrap_or_else(ScoreCalculator::get_default);
-        let group = self
-            .group_service
-            .get(
-                self.group_participation_service
-                    .get(group_participation_id)
-                    .expect("Error occurred getting group participation")
-                    .group_id,
-            )
-            .expect("Error occurred getting group");
+    /// Calculates the results for a season, including all of its competitions and their events
+    pub fn calculate_season(&self, season_id: Uuid) -> LuaResult<SeasonResult> {
+        let season = self
+            .season_service
+            .get(season_id)
+            .expect("Error occurred getting season");
+        let participants = self.list_participants(season_id);
+
+        let mut competitions = Vec::new();
+        let mut competition_values: HashMap<Uuid, Vec<LuaValue>> = HashMap::new();
+        for season_competition in self
+            .season_competition_service
+            .list_in_season(season_id)
+            .into_iter()
+            .filter(|season_competition| season_competition.enabled)
+        {
+            let (competition_result, mut season_data) =
+                self.calculate_competition_scores(&season_competition, &participants)?;
+            competitions.push(competition_result);
+
+            // data for teams is passed to the calculator for the group they belong to
+            for participant in &participants {
+                if let Some(value) = season_data.remove(&participant.id) {
+                    competition_values
+                        .entry(participant.group_participation_id)
+                        .or_default()
+                        .push(value);
+                }
+            }
+        }
+
+        let groups: Vec<&Participant> = participants
+            .iter()
+            .filter(|participant| participant.is_type(&ScoreType::Group))
+            .collect();
 
-        todo!()
+        let input = self.lua.create_table()?;
+        for (index, group) in groups.iter().enumerate() {
+            let item = self.lua.create_table()?;
+            item.set("id", group.id.to_string())?;
+            item.set(
+                "data",
+                self.lua.create_sequence_from(
+                    competition_values.remove(&group.id).unwrap_or_default(),
+                )?,
+            )?;
+            input.raw_set(index + 1, item)?;
+        }
+
+        let output = self.run_calculator(
+            CalculationLevel::Season,
+            season.score_calculator,
+            &season.calculator_config,
+            input,
+        )?;
+
+        Ok(SeasonResult {
+            season_id,
+            season_name: season.name,
+            results: Self::rank_results(
+                &groups,
+                &output,
+                RankingPolicy::new(season.ranking_method, season.ranking_direction),
+            ),
+            under_protest: competitions
+                .iter()
+                .any(|competition| competition.under_protest),
+            competitions,
+        })
     }
 
-    pub fn calculate_team_competition_score(
+    /// Calculates the results for a season competition, including all of its events
+    pub fn calculate_season_competition(
         &self,
         season_competition_id: Uuid,
-        team_id: Uuid,
-        event_values: &[LuaValue],
-    ) -> (Option<Decimal>, LuaValue) {
+    ) -> LuaResult<CompetitionResult> {
         let season_competition = self
             .season_competition_service
             .get(season_competition_id)
             .expect("Error occurred getting season competition");
-        let score_calculator = season_competition
-            .score_calculator
-            .and_then(|score_calculator| self.score_calculator_service.get(score_calculator))
-            .unwrap_or_else(ScoreCalculator::get_default);
-        let team = self
-            .team_service
-            .get(team_id)
-            .expect("Error occurred getting team");
-        let group = self
-            .group_service
-            .get(
-                self.group_participation_service
-                    .get(team.group_participation_id)
-                    .expect("Error occurred getting group participation")
-                    .group_id,
-            )
-            .expect("Error occurred getting group");
-
-        todo!()
-    }
-
-    pub fn calculate_group_competition_score(
+        let participants = self.list_participants(season_competition.season_id);
+
+        self.calculate_competition_scores(&season_competition, &participants)
+            .map(|(result, _)| result)
+    }
+
+    /// Calculates the results for a competition event
+    pub fn calculate_competition_event(
         &self,
-        season_competition_id: Uuid,
-        group_participation_id: Uuid,
-        event_values: &[LuaValue],
-    ) -> (Option<Decimal>, LuaValue) {
+        competition_event_id: Uuid,
+    ) -> LuaResult<EventResult> {
+        let competition_event = self
+            .competition_event_service
+            .get(competition_event_id)
+            .expect("Error occurred getting competition event");
         let season_competition = self
             .season_competition_service
-            .get(season_competition_id)
+            .get(competition_event.season_competition_id)
             .expect("Error occurred getting season competition");
-        let score_calculator = season_competition
-            .score_calculator
-            .and_then(|score_calculator| self.score_calculator_service.get(score_calculator))
-            .unwrap_or_else(ScoreCalculator::get_default);
-        let group = self
-            .group_service
-            .get(
-                self.group_participation_service
-                    .get(group_participation_id)
-                    .expect("Error occurred getting group participation")
-                    .group_id,
-            )
-            .expect("Error occurred getting group");
+        let participants = self.list_participants(season_competition.season_id);
 
-        todo!()
+        self.calculate_event_scores(&competition_event, &participants)
+            .map(|(result, _)| result)
     }
 
-    pub fn calculate_group_season_score(
+    /// Calculates season competition results, returning them along with the season data for each team/group
+    fn calculate_competition_scores(
         &self,
-        season_id: Uuid,
-        group_participation_id: Uuid,
-        competition_values: &[LuaValue],
-    ) -> Option<Decimal> {
-        let season = self
-            .season_service
-            .get(season_id)
-            .expect("Error occurred getting season");
-        let score_calculator = season
-            .score_calculator
+        season_competition: &SeasonCompetition,
+        participants: &[Participant],
+    ) -> LuaResult<(CompetitionResult, HashMap<Uuid, LuaValue<'_>>)> {
+        let season_competition_id = season_competition.id.unwrap();
+        let competition = self
+            .competition_service
+            .get(season_competition.competition_id)
+            .expect("Error occurred getting competition");
+
+        let mut events = Vec::new();
+        let mut event_values: HashMap<Uuid, Vec<LuaValue>> = HashMap::new();
+        for competition_event in self
+            .competition_event_service
+            .list_in_season_competition(season_competition_id)
+            .into_iter()
+            .filter(|competition_event| self.is_enabled(competition_event))
+        {
+            let (event_result, competition_data) =
+                self.calculate_event_scores(&competition_event, participants)?;
+            events.push(event_result);
+
+            for (id, value) in competition_data {
+                event_values.entry(id).or_default().push(value);
+            }
+        }
+
+        // only teams/groups that took part in at least one event are included
+        let competitors: Vec<&Participant> = participants
+            .iter()
+            .filter(|participant| event_values.contains_key(&participant.id))
+            .collect();
+
+        let input = self.lua.create_table()?;
+        for (index, competitor) in competitors.iter().enumerate() {
+            let item = self.lua.create_table()?;
+            item.set("id", competitor.id.to_string())?;
+            item.set("type", competitor.type_name())?;
+            item.set(
+                "data",
+                self.lua.create_sequence_from(
+                    event_values.remove(&competitor.id).unwrap_or_default(),
+                )?,
+            )?;
+            input.raw_set(index + 1, item)?;
+        }
+
+        let output = self.run_calculator(
+            CalculationLevel::Competition,
+            season_competition.score_calculator,
+            &season_competition.calculator_config,
+            input,
+        )?;
+
+        let result = CompetitionResult {
+            season_competition_id,
+            competition_name: competition.name,
+            results: Self::rank_results(
+                &competitors,
+                &output,
+                RankingPolicy::new(
+                    season_competition.ranking_method,
+                    season_competition.ranking_direction,
+                ),
+            ),
+            under_protest: events.iter().any(|event| event.under_protest),
+            events,
+        };
+        let season_data = output
+            .into_iter()
+            .map(|(id, (_, value))| (id, value))
+            .collect();
+
+        Ok((result, season_data))
+    }
+
+    /// Calculates competition event results, returning them along with the competition data for each team/group
+    fn calculate_event_scores(
+        &self,
+        competition_event: &CompetitionEvent,
+        participants: &[Participant],
+    ) -> LuaResult<(EventResult, HashMap<Uuid, LuaValue<'_>>)> {
+        let competition_event_id = competition_event.id.unwrap();
+        let event = self
+            .event_service
+            .get(competition_event.event_id)
+            .expect("Error occurred getting event");
+
+        let competitors: Vec<&Participant> = participants
+            .iter()
+            .filter(|participant| participant.is_type(&competition_event.score_type))
+            .collect();
+
+        let scores = self
+            .score_service
+            .list_in_competition_event(competition_event_id);
+        let penalties = self
+            .penalty_service
+            .list_in_competition_event(competition_event_id);
+        let penalty_codes: HashMap<Uuid, PenaltyCode> = self
+            .penalty_code_service
+            .list()
+            .into_iter()
+            .map(|penalty_code| (penalty_code.id.unwrap(), penalty_code))
+            .collect();
+
+        let input = self.lua.create_table()?;
+        for (index, competitor) in competitors.iter().enumerate() {
+            let mut competitor_scores: Vec<_> = scores
+                .iter()
+                .filter(|score| {
+                    score.valid
+                        && score.subject_id == competitor.id
+                        && match score.verification_state {
+                            VerificationState::Entered => !competition_event.verified_scores_only,
+                            VerificationState::Verified => true,
+                            VerificationState::Rejected => false,
+                        }
+                })
+                .collect();
+            competitor_scores.sort_by_key(|score| Reverse(score.timestamp));
+
+            // scores grouped by judge, in the order of each judge's newest score
+            let mut judges: Vec<(Option<Uuid>, Vec<&Score>)> = Vec::new();
+            for score in &competitor_scores {
+                match judges
+                    .iter_mut()
+                    .find(|(judge_id, _)| *judge_id == score.judge_id)
+                {
+                    Some((_, judge_scores)) => judge_scores.push(score),
+                    None => judges.push((score.judge_id, vec![score])),
+                }
+            }
+
+            let judges_table = self.lua.create_table()?;
+            for (judge_index, (judge_id, judge_scores)) in judges.iter().enumerate() {
+                let judge = self.lua.create_table()?;
+                judge.set("id", judge_id.map(|judge_id| judge_id.to_string()))?;
+                judge.set("scores", self.scores_to_lua(judge_scores)?)?;
+                judges_table.raw_set(judge_index + 1, judge)?;
+            }
+
+            let item = self.lua.create_table()?;
+            item.set("id", competitor.id.to_string())?;
+            item.set("type", competitor.type_name())?;
+            item.set("scores", self.scores_to_lua(&competitor_scores)?)?;
+            item.set("judges", judges_table)?;
+            item.set(
+                "penalties",
+                self.penalties_to_lua(
+                    penalties
+                        .iter()
+                        .filter(|penalty| penalty.subject_id == competitor.id),
+                    &penalty_codes,
+                )?,
+            )?;
+            input.raw_set(index + 1, item)?;
+        }
+
+        let output = self.run_calculator(
+            CalculationLevel::Event,
+            competition_event.score_calculator,
+            &competition_event.calculator_config,
+            input,
+        )?;
+
+        let result = EventResult {
+            competition_event_id,
+            event_name: event.name,
+            results: Self::rank_results(
+                &competitors,
+                &output,
+                RankingPolicy::new(
+                    competition_event.ranking_method,
+                    competition_event.ranking_direction,
+                ),
+            ),
+            under_protest: self.protest_service.has_open_protests(competition_event_id),
+            penalties: penalties
+                .iter()
+                .filter(|penalty| {
+                    competitors
+                        .iter()
+                        .any(|competitor| competitor.id == penalty.subject_id)
+                })
+                .map(|penalty| {
+                    let penalty_code = penalty_codes.get(&penalty.penalty_code_id);
+                    PenaltyResult {
+                        penalty_id: penalty.id.unwrap(),
+                        score_type: penalty.score_type,
+                        subject_id: penalty.subject_id,
+                        code: penalty_code
+                            .map(|penalty_code| penalty_code.code.clone())
+                            .unwrap_or_default(),
+                        description: penalty_code
+                            .map(|penalty_code| penalty_code.description.clone())
+                            .unwrap_or_default(),
+                        penalty_type: penalty.penalty_type,
+                        value: penalty.value,
+                        notes: penalty.notes.clone(),
+                    }
+                })
+                .collect(),
+        };
+        let competition_data = competitors
+            .iter()
+            .map(|competitor| {
+                let value = output
+                    .get(&competitor.id)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or(LuaValue::Nil);
+                (competitor.id, value)
+            })
+            .collect();
+
+        Ok((result, competition_data))
+    }
+
+    /// Checks if a competition event and its event are enabled
+    fn is_enabled(&self, competition_event: &CompetitionEvent) -> bool {
+        competition_event.enabled
+            && self
+                .event_service
+                .get(competition_event.event_id)
+                .is_some_and(|event| event.enabled)
+    }
+
+    /// Gets all enabled group participations and enabled teams in a season, excluding those of disabled groups
+    fn list_participants(&self, season_id: Uuid) -> Vec<Participant> {
+        let mut participants = Vec::new();
+
+        for group_participation in self
+            .group_participation_service
+            .list_in_season(season_id)
+            .into_iter()
+            .filter(|group_participation| group_participation.enabled)
+        {
+            let group_participation_id = group_participation.id.unwrap();
+            let group = self
+                .group_service
+                .get(group_participation.group_id)
+                .expect("Error occurred getting group");
+            if !group.enabled {
+                continue;
+            }
+
+            participants.push(Participant {
+                score_type: ScoreType::Group,
+                id: group_participation_id,
+                group_participation_id,
+                group_name: group.name.clone(),
+                team_name: String::new(),
+            });
+
+            for team in self
+                .team_service
+                .list_in_group_participation(group_participation_id)
+                .into_iter()
+                .filter(|team| team.enabled)
+            {
+                participants.push(Participant {
+                    score_type: ScoreType::Team,
+                    id: team.id.unwrap(),
+                    group_participation_id,
+                    group_name: group.name.clone(),
+                    team_name: team.name,
+                });
+            }
+        }
+
+        participants
+    }
+
+    /// Runs a score calculator function with sample input without reading or saving any results
+    pub fn test_calculator(
+        &self,
+        score_calculator: &ScoreCalculator,
+        level: CalculationLevel,
+        input: &JsonValue,
+        config: &JsonValue,
+        ranking: RankingPolicy,
+    ) -> CalculatorTestResult {
+        let output = match self
+            .to_lua(input)
+            .and_then(|input| self.call_calculator(level, score_calculator, config, input))
+        {
+            Ok(output) => output,
+            Err(err) => {
+                return CalculatorTestResult {
+                    output: None,
+                    ranks: Vec::new(),
+                    error: Some(err.to_string()),
+                }
+            }
+        };
+
+        let output_json = match Self::to_json(&output, 0, &mut OutputConversion::default()) {
+            Ok(output_json) => output_json,
+            Err(err) => {
+                return CalculatorTestResult {
+                    output: None,
+                    ranks: Vec::new(),
+                    error: Some(err.to_string()),
+                }
+            }
+        };
+
+        match Self::parse_output(level, output) {
+            Ok(items) => {
+                let mut ranked: Vec<(String, RankKey)> =
+                    items.into_iter().map(|(id, key, _)| (id, key)).collect();
+                ranked.sort_by(|(_, a), (_, b)| ranking.compare(a, b));
+
+                let keys: Vec<RankKey> = ranked.iter().map(|(_, key)| key.clone()).collect();
+                let ranks = ranked
+                    .into_iter()
+                    .zip(ranking.rank(&keys))
+                    .map(|((id, key), rank)| TestRank {
+                        id,
+                        score: key.score,
+                        tiebreakers: key.tiebreakers,
+                        rank,
+                    })
+                    .collect();
+
+                CalculatorTestResult {
+                    output: Some(output_json),
+                    ranks,
+                    error: None,
+                }
+            }
+            Err(err) => CalculatorTestResult {
+                output: Some(output_json),
+                ranks: Vec::new(),
+                error: Some(err.to_string()),
+            },
+        }
+    }
+
+    /// Runs a score calculator function, returning the score and data for each team/group by id
+    fn run_calculator<'lua>(
+        &'lua self,
+        level: CalculationLevel,
+        score_calculator_id: Option<Uuid>,
+        config: &JsonValue,
+        input: Table<'lua>,
+    ) -> LuaResult<CalculatorOutput<'lua>> {
+        let score_calculator = score_calculator_id
             .and_then(|score_calculator| self.score_calculator_service.get(score_calculator))
             .unwrap_or_else(ScoreCalculator::get_default);
-        let group = self
-            .group_service
-            .get(
-                self.group_participation_service
-                    .get(group_participation_id)
-                    .expect("Error occurred getting group participation")
-                    .group_id,
-            )
-            .expect("Error occurred getting group");
-
-        todo!()
+
+        let output =
+            self.call_calculator(level, &score_calculator, config, LuaValue::Table(input))?;
+
+        Self::parse_output(level, output)?
+            .into_iter()
+            .map(|(id, key, data)| {
+                let id = Uuid::parse_str(&id).map_err(|err| {
+                    LuaError::runtime(format!(
+                        "Invalid id \"{}\" returned from {}: {}",
+                        id,
+                        level.function_name(),
+                        err
+                    ))
+                })?;
+                Ok((id, (key, data)))
+            })
+            .collect()
+    }
+
+    /// Loads a score calculator and calls the function for the level, returning the raw output
+    fn call_calculator<'lua>(
+        &'lua self,
+        level: CalculationLevel,
+        score_calculator: &ScoreCalculator,
+        config: &JsonValue,
+        input: LuaValue<'lua>,
+    ) -> LuaResult<LuaValue<'lua>> {
+        if !level.is_supported_by(score_calculator) {
+            return Err(LuaError::runtime(format!(
+                "Score calculator \"{}\" does not support {}",
+                score_calculator.name,
+                level.function_name()
+            )));
+        }
+
+        let environment =
+            sandbox::load_script(&self.lua, &score_calculator.name, &score_calculator.script)?;
+        let function: Function = match environment.get(level.function_name())? {
+            LuaValue::Function(function) => function,
+            _ => {
+                return Err(LuaError::runtime(format!(
+                    "Score calculator \"{}\" does not define function \"{}\"",
+                    score_calculator.name,
+                    level.function_name()
+                )))
+            }
+        };
+
+        let config = Self::merge_config(&score_calculator.default_config, config);
+        function.call((input, self.to_lua(&config)?))
+    }
+
+    /// Parses the array returned from a score calculator function into the id, score with tiebreakers and data of each item
+    fn parse_output(
+        level: CalculationLevel,
+        output: LuaValue<'_>,
+    ) -> LuaResult<Vec<(String, RankKey, LuaValue<'_>)>> {
+        let LuaValue::Table(output) = output else {
+            return Err(LuaError::runtime(format!(
+                "{} must return an array, got {}",
+                level.function_name(),
+                output.type_name()
+            )));
+        };
+
+        let mut items = Vec::new();
+        for item in output.sequence_values::<Table>() {
+            let item = item?;
+
+            let id: String = item.get("id")?;
+            let score = Self::to_decimal(item.get(level.score_key())?)?;
+            let tiebreakers = match item.get("tiebreakers")? {
+                Some(tiebreakers) => Self::to_decimals(tiebreakers)?,
+                None => Vec::new(),
+            };
+            let data = match level.data_key() {
+                Some(data_key) => item.get(data_key)?,
+                None => LuaValue::Nil,
+            };
+
+            items.push((id, RankKey { score, tiebreakers }, data));
+        }
+
+        Ok(items)
+    }
+
+    /// Converts a Lua value to JSON for displaying calculator output.
+    /// Tables that appear more than once are only converted the first time, and an error is returned if the output is too large.
+    fn to_json(
+        value: &LuaValue,
+        depth: usize,
+        conversion: &mut OutputConversion,
+    ) -> LuaResult<JsonValue> {
+        conversion.values += 1;
+        if conversion.values > MAX_OUTPUT_VALUES {
+            return Err(LuaError::runtime(format!(
+                "Output has more than {} values",
+                MAX_OUTPUT_VALUES
+            )));
+        }
+
+        Ok(match value {
+            LuaValue::Nil => JsonValue::Null,
+            LuaValue::Boolean(value) => JsonValue::Bool(*value),
+            LuaValue::Integer(value) => JsonValue::from(*value),
+            LuaValue::Number(value) => JsonValue::from(*value),
+            LuaValue::String(value) => JsonValue::String(value.to_string_lossy().into_owned()),
+            LuaValue::Table(_) if depth >= MAX_OUTPUT_DEPTH => {
+                JsonValue::String(String::from("<max depth exceeded>"))
+            }
+            LuaValue::Table(table) if !conversion.visited.insert(table.to_pointer()) => {
+                JsonValue::String(String::from("<repeated table>"))
+            }
+            LuaValue::Table(table) => {
+                let pairs: Vec<(LuaValue, LuaValue)> =
+                    table.clone().pairs().filter_map(|pair| pair.ok()).collect();
+
+                if pairs.len() == table.raw_len() {
+                    JsonValue::Array(
+                        table
+                            .clone()
+                            .sequence_values::<LuaValue>()
+                            .map(|value| match value {
+                                Ok(value) => Self::to_json(&value, depth + 1, conversion),
+                                Err(_) => Ok(JsonValue::Null),
+                            })
+                            .collect::<LuaResult<_>>()?,
+                    )
+                } else {
+                    JsonValue::Object(
+                        pairs
+                            .iter()
+                            .map(|(key, value)| {
+                                let key = match key {
+                                    LuaValue::String(key) => key.to_string_lossy().into_owned(),
+                                    key => Self::to_json(key, depth + 1, conversion)?.to_string(),
+                                };
+                                Ok((key, Self::to_json(value, depth + 1, conversion)?))
+                            })
+                            .collect::<LuaResult<_>>()?,
+                    )
+                }
+            }
+            LuaValue::UserData(value) => match value.borrow::<DecimalValue>() {
+                Ok(value) => JsonValue::String(value.value().to_string()),
+                Err(_) => JsonValue::String(String::from("<userdata>")),
+            },
+            value => JsonValue::String(format!("<{}>", value.type_name())),
+        })
+    }
+
+    /// Converts a score returned from a score calculator to a decimal
+    fn to_decimal(value: LuaValue) -> LuaResult<Option<Decimal>> {
+        match value {
+            LuaValue::Nil => Ok(None),
+            value => Ok(Some(DecimalValue::from_value(&value)?.value())),
+        }
+    }
+
+    /// Converts an array returned from a score calculator to decimals, keeping nil items
+    fn to_decimals(values: Table) -> LuaResult<Vec<Option<Decimal>>> {
+        (1..=values.raw_len())
+            .map(|index| Self::to_decimal(values.raw_get(index)?))
+            .collect()
+    }
+
+    /// Converts scores to an array of score data, with nil for disqualified scores
+    fn scores_to_lua(&self, scores: &[&Score]) -> LuaResult<Table<'_>> {
+        let table = self.lua.create_table()?;
+        for (index, score) in scores.iter().enumerate() {
+            let score_data = if score.disqualified {
+                LuaValue::Nil
+            } else {
+                self.to_lua(&score.score_data)?
+            };
+            table.raw_set(index + 1, score_data)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Converts penalties to an array of penalty tables with their reason codes
+    fn penalties_to_lua<'a>(
+        &self,
+        penalties: impl Iterator<Item = &'a Penalty>,
+        penalty_codes: &HashMap<Uuid, PenaltyCode>,
+    ) -> LuaResult<Table<'_>> {
+        let table = self.lua.create_table()?;
+        for (index, penalty) in penalties.enumerate() {
+            let item = self.lua.create_table()?;
+            item.set(
+                "code",
+                penalty_codes
+                    .get(&penalty.penalty_code_id)
+                    .map(|penalty_code| penalty_code.code.clone()),
+            )?;
+            item.set(
+                "type",
+                match penalty.penalty_type {
+                    PenaltyType::PointDeduction => "PointDeduction",
+                    PenaltyType::TimeAddition => "TimeAddition",
+                    PenaltyType::Disqualification => "Disqualification",
+                },
+            )?;
+            item.set("value", penalty.value.map(DecimalValue::new))?;
+            item.set("notes", penalty.notes.clone())?;
+            table.raw_set(index + 1, item)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Converts JSON to a Lua value, decoding JSON-encoded strings and converting nulls to nil
+    fn to_lua(&self, value: &JsonValue) -> LuaResult<LuaValue<'_>> {
+        self.lua.to_value_with(
+            &decode_json_string(value),
+            SerializeOptions::new()
+                .serialize_none_to_null(false)
+                .serialize_unit_to_null(false),
+        )
+    }
+
+    /// Overrides the default config of a score calculator with the provided config
+    fn merge_config(default_config: &JsonValue, config: &JsonValue) -> JsonValue {
+        match (
+            &decode_json_string(default_config),
+            &decode_json_string(config),
+        ) {
+            (JsonValue::Object(default_config), JsonValue::Object(config)) => {
+                let mut merged = default_config.clone();
+                merged.extend(config.clone());
+                JsonValue::Object(merged)
+            }
+            (default_config, JsonValue::Null) => default_config.clone(),
+            (_, config) => config.clone(),
+        }
+    }
+
+    /// Ranks teams/groups using the ranking policy, with teams and groups ranked separately
+    fn rank_results(
+        participants: &[&Participant],
+        output: &CalculatorOutput<'_>,
+        ranking: RankingPolicy,
+    ) -> Vec<ScoreResult> {
+        let mut results = Vec::new();
+
+        for score_type in [ScoreType::Group, ScoreType::Team] {
+            let mut ranked: Vec<(&Participant, RankKey)> = participants
+                .iter()
+                .filter(|participant| participant.is_type(&score_type))
+                .map(|participant| {
+                    let key = output
+                        .get(&participant.id)
+                        .map(|(key, _)| key.clone())
+                        .unwrap_or_default();
+                    (*participant, key)
+                })
+                .collect();
+
+            ranked.sort_by(|(a, a_key), (b, b_key)| {
+                ranking
+                    .compare(a_key, b_key)
+                    .then_with(|| a.group_name.cmp(&b.group_name))
+                    .then_with(|| a.team_name.cmp(&b.team_name))
+            });
+
+            let keys: Vec<RankKey> = ranked.iter().map(|(_, key)| key.clone()).collect();
+            results.extend(
+                ranked
+                    .iter()
+                    .zip(ranking.rank(&keys))
+                    .map(|((participant, key), rank)| participant.to_result(key.score, rank)),
+            );
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_json(script: &str) -> LuaResult<JsonValue> {
+        let lua = Lua::new();
+        DecimalValue::add_constructor(&lua, "decimal");
+        let output: LuaValue = lua.load(script).eval().unwrap();
+        ResultsCalculator::to_json(&output, 0, &mut OutputConversion::default())
+    }
+
+    #[test]
+    fn output_is_converted_to_json() {
+        assert_eq!(
+            output_json("return {{id = 'a', event_score = decimal('1.50'), data = {1, 2}}}")
+                .unwrap(),
+            serde_json::json!([{"id": "a", "event_score": "1.50", "data": [1, 2]}])
+        );
+    }
+
+    #[test]
+    fn repeated_tables_are_converted_once() {
+        // each level refers to the next twice, which would be 2^32 values if expanded
+        let output = output_json("local t = {} for i = 1, 32 do t = {t, t} end return t").unwrap();
+
+        assert_eq!(
+            output[1],
+            JsonValue::String(String::from("<repeated table>"))
+        );
+        assert_eq!(
+            output[0][1],
+            JsonValue::String(String::from("<repeated table>"))
+        );
+    }
+
+    #[test]
+    fn cycles_are_not_followed() {
+        let output = output_json("local t = {name = 'loop'} t.next = t return t").unwrap();
+
+        assert_eq!(
+            output,
+            serde_json::json!({"name": "loop", "next": "<repeated table>"})
+        );
+    }
+
+    #[test]
+    fn large_output_is_an_error() {
+        let error =
+            output_json("local t = {} for i = 1, 200000 do t[i] = i end return t").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains(&format!("more than {} values", MAX_OUTPUT_VALUES)));
+    }
+
+    #[test]
+    fn deep_output_is_truncated() {
+        let output = output_json("local t = {} for i = 1, 100 do t = {t} end return t").unwrap();
+
+        let mut value = &output;
+        for _ in 0..MAX_OUTPUT_DEPTH {
+            value = &value[0];
+        }
+        assert_eq!(
+            value,
+            &JsonValue::String(String::from("<max depth exceeded>"))
+        );
     }
 }
@@ -0,0 +1,280 @@
+use std::cmp::Ordering;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::database::ranking::{RankingDirection, RankingMethod};
+
+/// How results are ordered and how ties are ranked
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct RankingPolicy {
+    #[serde(default)]
+    pub method: RankingMethod,
+    #[serde(default)]
+    pub direction: RankingDirection,
+}
+impl RankingPolicy {
+    pub fn new(method: RankingMethod, direction: RankingDirection) -> Self {
+        Self { method, direction }
+    }
+
+    /// Compares the score and then each tiebreaker in order, with the better item ordered first.
+    /// Missing values are ordered after any value.
+    pub fn compare(&self, a: &RankKey, b: &RankKey) -> Ordering {
+        self.compare_value(&a.score, &b.score).then_with(|| {
+            let length = a.tiebreakers.len().max(b.tiebreakers.len());
+            (0..length)
+                .map(|index| {
+                    self.compare_value(
+                        &a.tiebreakers.get(index).copied().flatten(),
+                        &b.tiebreakers.get(index).copied().flatten(),
+                    )
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    fn compare_value(&self, a: &Option<Decimal>, b: &Option<Decimal>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => match self.direction {
+                RankingDirection::HighestWins => b.cmp(a),
+                RankingDirection::LowestWins => a.cmp(b),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Ranks keys that have already been sorted with `compare`.
+    /// Items that compare equal are tied, and items without a score are not ranked.
+    pub fn rank(&self, keys: &[RankKey]) -> Vec<Option<Decimal>> {
+        let mut ranks = Vec::with_capacity(keys.len());
+
+        let mut dense_rank = 0;
+        let mut start = 0;
+        while start < keys.len() {
+            let end = keys[start..]
+                .iter()
+                .position(|key| self.compare(&keys[start], key).is_ne())
+                .map_or(keys.len(), |length| start + length);
+            dense_rank += 1;
+
+            let rank = match self.method {
+                RankingMethod::Standard => Decimal::from(start + 1),
+                RankingMethod::Dense => Decimal::from(dense_rank),
+                RankingMethod::Fractional => {
+                    (Decimal::from(start + 1 + end) / Decimal::TWO).normalize()
+                }
+            };
+            for key in &keys[start..end] {
+                ranks.push(key.score.map(|_| rank));
+            }
+
+            start = end;
+        }
+
+        ranks
+    }
+}
+
+/// Score and tiebreaker values returned from a score calculator for a team/group
+#[derive(Clone, Default)]
+pub struct RankKey {
+    pub score: Option<Decimal>,
+    pub tiebreakers: Vec<Option<Decimal>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(score: Option<i64>, tiebreakers: &[Option<i64>]) -> RankKey {
+        RankKey {
+            score: score.map(Decimal::from),
+            tiebreakers: tiebreakers
+                .iter()
+                .map(|tiebreaker| tiebreaker.map(Decimal::from))
+                .collect(),
+        }
+    }
+
+    /// Sorts the keys with the policy and returns their scores and ranks in order
+    fn ranked(
+        method: RankingMethod,
+        direction: RankingDirection,
+        mut keys: Vec<RankKey>,
+    ) -> Vec<(Option<Decimal>, Option<String>)> {
+        let policy = RankingPolicy::new(method, direction);
+        keys.sort_by(|a, b| policy.compare(a, b));
+        let ranks = policy.rank(&keys);
+        keys.iter()
+            .zip(ranks)
+            .map(|(key, rank)| (key.score, rank.map(|rank| rank.to_string())))
+            .collect()
+    }
+
+    fn ranks(
+        method: RankingMethod,
+        direction: RankingDirection,
+        keys: Vec<RankKey>,
+    ) -> Vec<String> {
+        ranked(method, direction, keys)
+            .into_iter()
+            .map(|(_, rank)| rank.unwrap_or_else(|| String::from("-")))
+            .collect()
+    }
+
+    fn tied_keys() -> Vec<RankKey> {
+        vec![
+            key(Some(5), &[]),
+            key(Some(10), &[]),
+            key(Some(10), &[]),
+            key(Some(3), &[]),
+        ]
+    }
+
+    #[test]
+    fn standard_ranking_skips_ranks_after_ties() {
+        assert_eq!(
+            ranks(
+                RankingMethod::Standard,
+                RankingDirection::HighestWins,
+                tied_keys()
+            ),
+            ["1", "1", "3", "4"]
+        );
+    }
+
+    #[test]
+    fn dense_ranking_does_not_skip_ranks() {
+        assert_eq!(
+            ranks(
+                RankingMethod::Dense,
+                RankingDirection::HighestWins,
+                tied_keys()
+            ),
+            ["1", "1", "2", "3"]
+        );
+    }
+
+    #[test]
+    fn fractional_ranking_averages_tied_ranks() {
+        assert_eq!(
+            ranks(
+                RankingMethod::Fractional,
+                RankingDirection::HighestWins,
+                tied_keys()
+            ),
+            ["1.5", "1.5", "3", "4"]
+        );
+        assert_eq!(
+            ranks(
+                RankingMethod::Fractional,
+                RankingDirection::HighestWins,
+                vec![key(Some(1), &[]), key(Some(1), &[]), key(Some(1), &[])]
+            ),
+            ["2", "2", "2"]
+        );
+    }
+
+    #[test]
+    fn direction_orders_scores() {
+        let scores = |direction| {
+            ranked(RankingMethod::Standard, direction, tied_keys())
+                .into_iter()
+                .map(|(score, _)| score.unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            scores(RankingDirection::HighestWins),
+            ["10", "10", "5", "3"]
+        );
+        assert_eq!(scores(RankingDirection::LowestWins), ["3", "5", "10", "10"]);
+    }
+
+    #[test]
+    fn missing_scores_are_last_and_unranked() {
+        for direction in [RankingDirection::HighestWins, RankingDirection::LowestWins] {
+            let result = ranked(
+                RankingMethod::Standard,
+                direction,
+                vec![key(None, &[]), key(Some(2), &[]), key(Some(1), &[])],
+            );
+            assert_eq!(result[2], (None, None));
+            assert!(result[..2]
+                .iter()
+                .all(|(score, rank)| score.is_some() && rank.is_some()));
+        }
+    }
+
+    #[test]
+    fn tiebreakers_break_ties_in_order() {
+        let result = ranked(
+            RankingMethod::Standard,
+            RankingDirection::HighestWins,
+            vec![
+                key(Some(10), &[Some(1), Some(5)]),
+                key(Some(10), &[Some(2), Some(0)]),
+                key(Some(10), &[Some(1), Some(7)]),
+                key(Some(9), &[Some(9), Some(9)]),
+            ],
+        );
+        assert_eq!(
+            result
+                .iter()
+                .map(|(_, rank)| rank.clone().unwrap())
+                .collect::<Vec<String>>(),
+            ["1", "2", "3", "4"]
+        );
+
+        let policy = RankingPolicy::new(RankingMethod::Standard, RankingDirection::HighestWins);
+        assert_eq!(
+            policy.compare(
+                &key(Some(10), &[Some(2), Some(0)]),
+                &key(Some(10), &[Some(1), Some(7)])
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn tiebreakers_use_ranking_direction() {
+        let policy = RankingPolicy::new(RankingMethod::Standard, RankingDirection::LowestWins);
+        assert_eq!(
+            policy.compare(&key(Some(10), &[Some(1)]), &key(Some(10), &[Some(2)])),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn missing_tiebreakers_are_ordered_last() {
+        let policy = RankingPolicy::new(RankingMethod::Standard, RankingDirection::HighestWins);
+        assert_eq!(
+            policy.compare(&key(Some(10), &[Some(1)]), &key(Some(10), &[None])),
+            Ordering::Less
+        );
+        assert_eq!(
+            policy.compare(&key(Some(10), &[Some(1)]), &key(Some(10), &[])),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn equal_tiebreakers_stay_tied() {
+        assert_eq!(
+            ranks(
+                RankingMethod::Dense,
+                RankingDirection::HighestWins,
+                vec![
+                    key(Some(10), &[Some(1)]),
+                    key(Some(10), &[Some(1)]),
+                    key(Some(10), &[Some(0)]),
+                ]
+            ),
+            ["1", "1", "2"]
+        );
+    }
+}
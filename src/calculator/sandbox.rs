@@ -0,0 +1,261 @@
+use mlua::{Error, HookTriggers, Lua, LuaOptions, Result, StdLib, Table, Value};
+
+use crate::config::AppConfig;
+
+/// Number of instructions between each instruction limit check
+const INSTRUCTION_CHECK_INTERVAL: u32 = 1000;
+
+/// Global functions removed from the sandbox since they can load code from files or bytecode
+const REMOVED_GLOBALS: &[&str] = &["dofile", "loadfile", "load", "collectgarbage"];
+
+/// Instructions executed by the script currently running in a sandbox
+struct InstructionCount {
+    executed: u64,
+    limit: u64,
+}
+
+/// Creates a restricted Lua state for running score calculator scripts.
+/// Only the coroutine, table, string, utf8 and math libraries are available,
+/// and scripts are stopped with an error if they exceed the configured instruction or memory limits.
+pub fn new(config: &AppConfig) -> Lua {
+    let lua = Lua::new_with(
+        StdLib::COROUTINE | StdLib::TABLE | StdLib::STRING | StdLib::UTF8 | StdLib::MATH,
+        LuaOptions::default(),
+    )
+    .expect("Error occurred creating Lua state");
+
+    for name in REMOVED_GLOBALS {
+        lua.globals()
+            .raw_remove(*name)
+            .expect("Error occurred removing global from Lua state");
+    }
+
+    // the string metatable indexes the shared string library, so it's hidden from scripts
+    lua.load("getmetatable('').__metatable = false")
+        .exec()
+        .expect("Error occurred protecting Lua string metatable");
+
+    lua.set_memory_limit(config.calculator_memory_limit)
+        .expect("Error occurred setting Lua memory limit");
+
+    lua.set_app_data(InstructionCount {
+        executed: 0,
+        limit: config.calculator_instruction_limit,
+    });
+    lua.set_hook(
+        HookTriggers::new().every_nth_instruction(INSTRUCTION_CHECK_INTERVAL),
+        |lua, _debug| {
+            let mut instruction_count = lua
+                .app_data_mut::<InstructionCount>()
+                .expect("Lua state is missing instruction count");
+            instruction_count.executed += u64::from(INSTRUCTION_CHECK_INTERVAL);
+
+            if instruction_count.executed > instruction_count.limit {
+                Err(Error::runtime(format!(
+                    "Score calculator exceeded the instruction limit of {}",
+                    instruction_count.limit
+                )))
+            } else {
+                Ok(())
+            }
+        },
+    );
+
+    lua
+}
+
+/// Loads a script into its own environment so scripts can't interfere with each other, returning the environment.
+/// Library tables such as `string` and `math` are copied into the environment, so changes a script makes to them don't affect other scripts.
+/// The instruction count is reset so each script run gets the full instruction limit.
+pub fn load_script<'lua>(lua: &'lua Lua, name: &str, script: &str) -> Result<Table<'lua>> {
+    reset_instruction_count(lua);
+
+    let globals = lua.globals();
+    let environment = lua.create_table()?;
+    for pair in globals.clone().pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        let value = match value {
+            Value::Table(table) if table != globals => Value::Table(copy_table(lua, &table)?),
+            value => value,
+        };
+        environment.raw_set(key, value)?;
+    }
+    environment.raw_set("_G", environment.clone())?;
+
+    lua.load(script)
+        .set_name(name)
+        .set_environment(environment.clone())
+        .exec()?;
+
+    Ok(environment)
+}
+
+/// Creates a shallow copy of a table, sharing its metatable
+fn copy_table<'lua>(lua: &'lua Lua, table: &Table<'lua>) -> Result<Table<'lua>> {
+    let copy = lua.create_table()?;
+    for pair in table.clone().pairs::<Value, Value>() {
+        let (key, value) = pair?;
+        copy.raw_set(key, value)?;
+    }
+    copy.set_metatable(table.get_metatable());
+    Ok(copy)
+}
+
+/// Resets the instruction count so the next script run gets the full instruction limit
+pub fn reset_instruction_count(lua: &Lua) {
+    if let Some(mut instruction_count) = lua.app_data_mut::<InstructionCount>() {
+        instruction_count.executed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::Function;
+
+    use super::*;
+    use crate::calculator::decimal::DecimalValue;
+
+    fn sandbox() -> Lua {
+        let config: AppConfig = serde_json::from_str(
+            r#"{"calculator_instruction_limit": 100000, "calculator_memory_limit": 4194304}"#,
+        )
+        .unwrap();
+        new(&config)
+    }
+
+    #[test]
+    fn instruction_limit_stops_infinite_loops() {
+        let lua = sandbox();
+
+        let error = load_script(&lua, "test", "while true do end").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("exceeded the instruction limit of 100000"));
+    }
+
+    #[test]
+    fn instruction_count_is_reset_for_each_script() {
+        let lua = sandbox();
+        let script = "local total = 0 for i = 1, 10000 do total = total + i end";
+
+        // each run is under the limit, but all of them together are over it
+        for _ in 0..20 {
+            load_script(&lua, "test", script).unwrap();
+        }
+    }
+
+    #[test]
+    fn instruction_limit_applies_to_functions_called_after_loading() {
+        let lua = sandbox();
+        let environment =
+            load_script(&lua, "test", "function run() while true do end end").unwrap();
+
+        reset_instruction_count(&lua);
+        let run: Function = environment.get("run").unwrap();
+        assert!(run.call::<_, ()>(()).is_err());
+    }
+
+    #[test]
+    fn memory_limit_stops_large_allocations() {
+        let lua = sandbox();
+
+        let error = load_script(
+            &lua,
+            "test",
+            "local items = {} for i = 1, 1000 do items[i] = string.rep('x', 65536) .. i end",
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::MemoryError(_)));
+
+        // the state can still be used after running out of memory
+        load_script(&lua, "test", "local x = string.rep('x', 1024)").unwrap();
+    }
+
+    #[test]
+    fn unsafe_globals_are_removed() {
+        let lua = sandbox();
+
+        for name in [
+            "io",
+            "os",
+            "package",
+            "debug",
+            "dofile",
+            "loadfile",
+            "load",
+            "collectgarbage",
+        ] {
+            let environment =
+                load_script(&lua, "test", &format!("present = {} ~= nil", name)).unwrap();
+            assert!(!environment.get::<_, bool>("present").unwrap(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn scripts_have_separate_environments() {
+        let lua = sandbox();
+
+        let first = load_script(&lua, "first", "value = 1").unwrap();
+        let second = load_script(&lua, "second", "present = value ~= nil").unwrap();
+
+        assert_eq!(first.get::<_, i64>("value").unwrap(), 1);
+        assert!(!second.get::<_, bool>("present").unwrap());
+        assert!(lua
+            .globals()
+            .get::<_, Option<i64>>("value")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn scripts_cannot_change_libraries_for_other_scripts() {
+        let lua = sandbox();
+
+        load_script(
+            &lua,
+            "first",
+            r#"
+            string.upper = function() return "changed" end
+            math.floor = nil
+            table.insert = nil
+            _G.string.lower = nil
+            string.other = true
+            "#,
+        )
+        .unwrap();
+        assert!(load_script(
+            &lua,
+            "metatable",
+            "getmetatable('').__index.upper = function() return 'changed' end"
+        )
+        .is_err());
+
+        DecimalValue::add_constructor(&lua, "decimal");
+        assert!(load_script(
+            &lua,
+            "decimal",
+            "getmetatable(decimal).__call = function() return 0 end"
+        )
+        .is_err());
+        let decimal = load_script(&lua, "decimal", "value = tostring(decimal('1.5'))").unwrap();
+        assert_eq!(decimal.get::<_, String>("value").unwrap(), "1.5");
+
+        let second = load_script(
+            &lua,
+            "second",
+            r#"
+            local items = {}
+            table.insert(items, math.floor(1.5))
+            upper = string.upper("a") .. ("b"):upper()
+            lower = string.lower("A")
+            floored = items[1]
+            other = string.other == nil
+            "#,
+        )
+        .unwrap();
+        assert_eq!(second.get::<_, String>("upper").unwrap(), "AB");
+        assert_eq!(second.get::<_, String>("lower").unwrap(), "a");
+        assert_eq!(second.get::<_, i64>("floored").unwrap(), 1);
+        assert!(second.get::<_, bool>("other").unwrap());
+    }
+}
@@ -0,0 +1,138 @@
+use mlua::{Error as LuaError, Value as LuaValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::AppConfig, database::score_calculators::ScoreCalculator};
+
+use super::{decimal::DecimalValue, sandbox, CalculationLevel};
+
+/// Problems found when validating a score calculator script
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScriptError {
+    /// Error message from Lua if the script failed to compile or run
+    pub message: Option<String>,
+    /// Line of the script that the error occurred on, if known
+    pub line: Option<u32>,
+    /// Calculation functions required by the supported levels that the script doesn't define
+    pub missing_functions: Vec<String>,
+}
+
+/// Compiles and runs a score calculator script, then checks that it defines the calculation functions for each level it supports
+pub fn validate_script(
+    config: &AppConfig,
+    score_calculator: &ScoreCalculator,
+) -> Result<(), ScriptError> {
+    let lua = sandbox::new(config);
+
+    DecimalValue::add_constructor(&lua, "decimal");
+
+    let environment =
+        match sandbox::load_script(&lua, &score_calculator.name, &score_calculator.script) {
+            Ok(environment) => environment,
+            Err(err) => {
+                let message = match err {
+                    LuaError::SyntaxError { message, .. } => message,
+                    err => err.to_string(),
+                };
+                return Err(ScriptError {
+                    line: error_line(&message),
+                    message: Some(message),
+                    missing_functions: Vec::new(),
+                });
+            }
+        };
+
+    let missing_functions: Vec<String> = [
+        CalculationLevel::Event,
+        CalculationLevel::Competition,
+        CalculationLevel::Season,
+    ]
+    .iter()
+    .filter(|level| level.is_supported_by(score_calculator))
+    .filter(|level| {
+        !matches!(
+            environment.get(level.function_name()),
+            Ok(LuaValue::Function(_))
+        )
+    })
+    .map(|level| String::from(level.function_name()))
+    .collect();
+
+    if missing_functions.is_empty() {
+        Ok(())
+    } else {
+        Err(ScriptError {
+            message: None,
+            line: None,
+            missing_functions,
+        })
+    }
+}
+
+/// Gets the line number from a Lua error message in the format `[string "name"]:<line>: <message>`
+fn error_line(message: &str) -> Option<u32> {
+    let (_, rest) = message.split_once("\"]:")?;
+    let (line, _) = rest.split_once(':')?;
+    line.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::builtin::builtin_calculators;
+
+    fn config() -> AppConfig {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    fn event_calculator(script: &str) -> ScoreCalculator {
+        ScoreCalculator {
+            id: None,
+            name: String::from("test"),
+            description: String::new(),
+            script: String::from(script),
+            default_config: serde_json::json!({}),
+            supports_seasons: false,
+            supports_competitions: false,
+            supports_events: true,
+            score_fields: None,
+        }
+    }
+
+    #[test]
+    fn scripts_can_use_decimal_when_loaded() {
+        let script = "local zero = decimal(0)
+function calculate_event_scores(teams, config)
+  return {}
+end";
+
+        assert!(validate_script(&config(), &event_calculator(script)).is_ok());
+    }
+
+    #[test]
+    fn builtin_calculators_are_valid() {
+        for score_calculator in builtin_calculators() {
+            assert!(
+                validate_script(&config(), &score_calculator).is_ok(),
+                "{}",
+                score_calculator.name
+            );
+        }
+    }
+
+    #[test]
+    fn missing_functions_are_reported() {
+        let error = validate_script(&config(), &event_calculator("local x = 1")).unwrap_err();
+
+        assert!(error.message.is_none());
+        assert_eq!(error.missing_functions, ["calculate_event_scores"]);
+    }
+
+    #[test]
+    fn errors_include_line_number() {
+        let error = validate_script(&config(), &event_calculator("local x = 1\nerror('failed')"))
+            .unwrap_err();
+
+        assert_eq!(error.line, Some(2));
+        assert!(error.message.unwrap().contains("failed"));
+    }
+}
@@ -37,6 +37,12 @@ pub struct AppConfig {
 
     #[serde(default = "default_http_caching_max_age")]
     pub http_caching_max_age: u64,
+
+    #[serde(default = "default_calculator_instruction_limit")]
+    pub calculator_instruction_limit: u64,
+
+    #[serde(default = "default_calculator_memory_limit")]
+    pub calculator_memory_limit: usize,
 }
 
 impl AppConfig {
@@ -95,3 +101,9 @@ fn default_enable_api_request_logging() -> bool {
 fn default_http_caching_max_age() -> u64 {
     60 * 60 * 24
 }
+fn default_calculator_instruction_limit() -> u64 {
+    10_000_000
+}
+fn default_calculator_memory_limit() -> usize {
+    32 * 1024 * 1024
+}
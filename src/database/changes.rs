@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Row, ToSql,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+impl ToSql for ChangeAction {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+        .into())
+    }
+}
+impl FromSql for ChangeAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("create") => Ok(Self::Create),
+            Ok("update") => Ok(Self::Update),
+            Ok("delete") => Ok(Self::Delete),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Entity that was created, updated or deleted
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub sequence: i64,
+    pub timestamp: DateTime<Utc>,
+    pub entity: String,
+    pub action: ChangeAction,
+    pub entity_id: Uuid,
+    /// Entity after it was created or updated, `None` if it was deleted
+    pub data: Option<JsonValue>,
+}
+impl Change {
+    pub const TABLE_NAME: &'static str = "changes";
+
+    pub const COLUMNS_SQL: &'static str =
+        "\"sequence\", \"timestamp\", \"entity\", \"action\", \"entity_id\", \"data\"";
+
+    pub fn from_row(row: &Row) -> Self {
+        Self {
+            sequence: row
+                .get("sequence")
+                .expect("Failed to get value from database row"),
+            timestamp: row
+                .get("timestamp")
+                .expect("Failed to get value from database row"),
+            entity: row
+                .get("entity")
+                .expect("Failed to get value from database row"),
+            action: row
+                .get("action")
+                .expect("Failed to get value from database row"),
+            entity_id: row
+                .get("entity_id")
+                .expect("Failed to get value from database row"),
+            data: row
+                .get("data")
+                .expect("Failed to get value from database row"),
+        }
+    }
+}
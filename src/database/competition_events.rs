@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
-use super::scores::ScoreType;
+use super::{
+    ranking::{RankingDirection, RankingMethod},
+    scores::ScoreType,
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CompetitionEvent {
@@ -15,12 +18,22 @@ pub struct CompetitionEvent {
     pub calculator_config: JsonValue,
     pub enabled: bool,
     pub score_type: ScoreType,
+    #[serde(default)]
+    pub ranking_method: RankingMethod,
+    #[serde(default)]
+    pub ranking_direction: RankingDirection,
+    /// Only use scores that have been verified when calculating results
+    #[serde(default)]
+    pub verified_scores_only: bool,
+    /// Scores can't be changed while locked, only set by the lock and unlock actions
+    #[serde(default)]
+    pub locked: bool,
 }
 impl CompetitionEvent {
     pub const TABLE_NAME: &'static str = "competition_events";
 
     pub const COLUMNS_SQL: &'static str =
-        "\"id\", \"season_competition_id\", \"event_id\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\", \"score_type\"";
+        "\"id\", \"season_competition_id\", \"event_id\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\", \"score_type\", \"ranking_method\", \"ranking_direction\", \"verified_scores_only\", \"locked\"";
 
     pub fn from_row(row: &Row) -> Self {
         Self {
@@ -48,6 +61,18 @@ impl CompetitionEvent {
             score_type: row
                 .get("score_type")
                 .expect("Failed to get value from database row"),
+            ranking_method: row
+                .get("ranking_method")
+                .expect("Failed to get value from database row"),
+            ranking_direction: row
+                .get("ranking_direction")
+                .expect("Failed to get value from database row"),
+            verified_scores_only: row
+                .get("verified_scores_only")
+                .expect("Failed to get value from database row"),
+            locked: row
+                .get("locked")
+                .expect("Failed to get value from database row"),
         }
     }
 }
@@ -0,0 +1,35 @@
+use rusqlite::Row;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Assignment of a user as a judge for either a single competition event or all events in a season competition
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JudgeAssignment {
+    pub id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub competition_event_id: Option<Uuid>,
+    pub season_competition_id: Option<Uuid>,
+}
+impl JudgeAssignment {
+    pub const TABLE_NAME: &'static str = "judge_assignments";
+
+    pub const COLUMNS_SQL: &'static str =
+        "\"id\", \"user_id\", \"competition_event_id\", \"season_competition_id\"";
+
+    pub fn from_row(row: &Row) -> Self {
+        Self {
+            id: row
+                .get("id")
+                .expect("Failed to get value from database row"),
+            user_id: row
+                .get("user_id")
+                .expect("Failed to get value from database row"),
+            competition_event_id: row
+                .get("competition_event_id")
+                .expect("Failed to get value from database row"),
+            season_competition_id: row
+                .get("season_competition_id")
+                .expect("Failed to get value from database row"),
+        }
+    }
+}
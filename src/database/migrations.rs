@@ -0,0 +1,163 @@
+use rusqlite::Connection;
+
+/// Schema changes to databases created with an earlier version of `database.sql`, in order.
+/// The database's `user_version` is the number of migrations that have been applied to it.
+/// New tables are created by `database.sql` itself, so migrations only need to change existing tables.
+const MIGRATIONS: &[&str] = &[
+    // ranking methods, score revisions, score verification, locks, judges and verified score only events
+    r#"
+ALTER TABLE "seasons" ADD COLUMN "ranking_method" TEXT NOT NULL DEFAULT 'standard';
+ALTER TABLE "seasons" ADD COLUMN "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins';
+ALTER TABLE "seasons" ADD COLUMN "locked" INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE "season_competitions" ADD COLUMN "ranking_method" TEXT NOT NULL DEFAULT 'standard';
+ALTER TABLE "season_competitions" ADD COLUMN "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins';
+ALTER TABLE "season_competitions" ADD COLUMN "locked" INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE "competition_events" ADD COLUMN "ranking_method" TEXT NOT NULL DEFAULT 'standard';
+ALTER TABLE "competition_events" ADD COLUMN "ranking_direction" TEXT NOT NULL DEFAULT 'highest_wins';
+ALTER TABLE "competition_events" ADD COLUMN "verified_scores_only" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "competition_events" ADD COLUMN "locked" INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE "group_scores" ADD COLUMN "original_id" BLOB REFERENCES "group_scores" ("id") ON DELETE CASCADE;
+ALTER TABLE "group_scores" ADD COLUMN "revision" INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE "group_scores" ADD COLUMN "entered_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "group_scores" ADD COLUMN "entered_at" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "group_scores" ADD COLUMN "modified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "group_scores" ADD COLUMN "modified_at" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "group_scores" ADD COLUMN "verification_state" TEXT NOT NULL DEFAULT 'entered';
+ALTER TABLE "group_scores" ADD COLUMN "verified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "group_scores" ADD COLUMN "verified_at" TEXT;
+ALTER TABLE "group_scores" ADD COLUMN "judge_id" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+UPDATE "group_scores" SET "entered_at" = "timestamp", "modified_at" = "timestamp";
+
+ALTER TABLE "team_scores" ADD COLUMN "original_id" BLOB REFERENCES "team_scores" ("id") ON DELETE CASCADE;
+ALTER TABLE "team_scores" ADD COLUMN "revision" INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE "team_scores" ADD COLUMN "entered_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "team_scores" ADD COLUMN "entered_at" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "team_scores" ADD COLUMN "modified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "team_scores" ADD COLUMN "modified_at" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "team_scores" ADD COLUMN "verification_state" TEXT NOT NULL DEFAULT 'entered';
+ALTER TABLE "team_scores" ADD COLUMN "verified_by" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "team_scores" ADD COLUMN "verified_at" TEXT;
+ALTER TABLE "team_scores" ADD COLUMN "judge_id" BLOB REFERENCES "users" ("id") ON DELETE SET NULL;
+UPDATE "team_scores" SET "entered_at" = "timestamp", "modified_at" = "timestamp";
+"#,
+];
+
+/// Gets the number of migrations that have been applied to a database
+fn get_version(conn: &Connection) -> usize {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version;", [], |row| row.get(0))
+        .expect("Error occurred getting database schema version");
+
+    version as usize
+}
+
+/// Applies each migration that hasn't been applied to an existing database, in its own transaction
+pub fn migrate(conn: &Connection) {
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(get_version(conn)) {
+        conn.execute_batch(&format!(
+            "BEGIN TRANSACTION;\n{}\nPRAGMA user_version = {};\nCOMMIT;",
+            migration,
+            index + 1
+        ))
+        .expect("Error occurred migrating database schema");
+    }
+}
+
+/// Marks a database that was just created from `database.sql` as having every migration applied
+pub fn set_latest_version(conn: &Connection) {
+    conn.execute_batch(&format!("PRAGMA user_version = {};", MIGRATIONS.len()))
+        .expect("Error occurred setting database schema version");
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        calculator::ResultsCalculator,
+        config::AppConfig,
+        database::{scores::VerificationState, Database},
+        services::{competition_events::CompetitionEventsService, scores::ScoresService},
+    };
+
+    const BASELINE_DATABASE_SQL: &str = include_str!("../../tests/fixtures/baseline_database.sql");
+
+    #[test]
+    fn baseline_database_is_migrated() {
+        let database_file =
+            std::env::temp_dir().join(format!("scorekeeper-migration-{}.sqlite3", Uuid::new_v4()));
+        let config: AppConfig = serde_json::from_value(serde_json::json!({
+            "database_file": database_file.to_string_lossy(),
+        }))
+        .unwrap();
+
+        let (season_id, competition_event_id, score_id) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        {
+            let conn = Connection::open(&database_file).unwrap();
+            conn.execute_batch(BASELINE_DATABASE_SQL).unwrap();
+            conn.execute_batch("PRAGMA foreign_keys = 1;").unwrap();
+            let ids = [
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+            ];
+            let [competition_id, season_competition_id, event_id, group_id, group_participation_id, team_id] =
+                ids;
+            for (sql, params) in [
+                ("INSERT INTO \"seasons\" (\"id\", \"name\") VALUES (?1, 'Season');", vec![season_id]),
+                ("INSERT INTO \"competitions\" (\"id\", \"name\") VALUES (?1, 'Competition');", vec![competition_id]),
+                ("INSERT INTO \"season_competitions\" (\"id\", \"season_id\", \"competition_id\") VALUES (?1, ?2, ?3);", vec![season_competition_id, season_id, competition_id]),
+                ("INSERT INTO \"events\" (\"id\", \"competition_id\", \"name\") VALUES (?1, ?2, 'Event');", vec![event_id, competition_id]),
+                ("INSERT INTO \"competition_events\" (\"id\", \"season_competition_id\", \"event_id\") VALUES (?1, ?2, ?3);", vec![competition_event_id, season_competition_id, event_id]),
+                ("INSERT INTO \"groups\" (\"id\", \"name\") VALUES (?1, 'Group');", vec![group_id]),
+                ("INSERT INTO \"group_participation\" (\"id\", \"group_id\", \"season_id\") VALUES (?1, ?2, ?3);", vec![group_participation_id, group_id, season_id]),
+                ("INSERT INTO \"teams\" (\"id\", \"group_participation_id\", \"name\") VALUES (?1, ?2, 'Team');", vec![team_id, group_participation_id]),
+                ("INSERT INTO \"team_scores\" (\"id\", \"competition_event_id\", \"team_id\", \"score_data\") VALUES (?1, ?2, ?3, '\"{\\\"Points\\\":\\\"5\\\"}\"');", vec![score_id, competition_event_id, team_id]),
+            ] {
+                conn.execute(sql, rusqlite::params_from_iter(params)).unwrap();
+            }
+        }
+
+        let database = Database::new(&config);
+        assert_eq!(get_version(&database.get()), MIGRATIONS.len());
+
+        let score = ScoresService::new(&database, &config)
+            .get(score_id)
+            .unwrap();
+        assert_eq!(score.revision, 1);
+        assert_eq!(score.entered_at, Some(score.timestamp));
+        assert!(matches!(
+            score.verification_state,
+            VerificationState::Entered
+        ));
+        assert!(score.original_id.is_none() && score.judge_id.is_none());
+
+        let competition_event = CompetitionEventsService::new(&database)
+            .get(competition_event_id)
+            .unwrap();
+        assert!(!competition_event.locked && !competition_event.verified_scores_only);
+
+        let results = ResultsCalculator::new(&database, &config)
+            .calculate_season(season_id)
+            .unwrap();
+        assert_eq!(results.competitions[0].events[0].results.len(), 1);
+
+        // opening the migrated database again doesn't migrate it again
+        drop(database);
+        let database = Database::new(&config);
+        assert_eq!(get_version(&database.get()), MIGRATIONS.len());
+        drop(database);
+
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", database_file.to_string_lossy(), suffix));
+        }
+    }
+}
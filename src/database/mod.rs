@@ -1,8 +1,14 @@
+pub mod changes;
 pub mod competition_events;
 pub mod competitions;
 pub mod events;
 pub mod group_participation;
 pub mod groups;
+pub mod judge_assignments;
+mod migrations;
+pub mod penalties;
+pub mod protests;
+pub mod ranking;
 pub mod score_calculators;
 pub mod scores;
 pub mod season_competitions;
@@ -18,6 +24,7 @@ use crate::{
     config::AppConfig,
     services::{
         audit::AuditService,
+        score_calculators::ScoreCalculatorsService,
         users::{User, UsersService},
     },
 };
@@ -58,6 +65,12 @@ impl Database {
             "teams",
             "group_scores",
             "team_scores",
+            "judge_assignments",
+            "penalty_codes",
+            "penalties",
+            "protests",
+            "score_sync_operations",
+            "changes",
             "log",
         ];
         let table_sql = format!("('{}')", tables.join("', '"));
@@ -70,10 +83,18 @@ impl Database {
             .expect("Error occurred while checking for database initialization");
         let tables_exist = table_count == tables.len() as i64;
 
+        // add new columns to tables created by an earlier version before creating any new tables and indexes
+        if table_count > 0 {
+            migrations::migrate(&conn);
+        }
+
         if !tables_exist {
             // create tables
             conn.execute_batch(DATABASE_DEFINITION_SQL)
                 .expect("Error occurred while running database initialization commands");
+            if table_count == 0 {
+                migrations::set_latest_version(&conn);
+            }
 
             let audit_service = AuditService::new(&db);
 
@@ -99,6 +120,8 @@ impl Database {
                         permission_view_results: true,
                         permission_view_scores: true,
                         permission_enter_scores: true,
+                        permission_verify_scores: true,
+                        permission_override_locks: true,
                         permission_view_registration: true,
                         permission_enter_registration: true,
                     })
@@ -106,6 +129,16 @@ impl Database {
 
                 audit_service.log_data(None, "default_user_created", json!({"user_id": user_id}));
             }
+
+            let score_calculator_ids = ScoreCalculatorsService::new(&db, config)
+                .import_builtin(false)
+                .expect("Error occurred creating built-in score calculators");
+
+            audit_service.log_data(
+                None,
+                "builtin_score_calculators_created",
+                json!({"ids": score_calculator_ids}),
+            );
         }
 
         db
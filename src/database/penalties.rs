@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Row, ToSql,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::scores::ScoreType;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PenaltyType {
+    PointDeduction,
+    TimeAddition,
+    Disqualification,
+}
+impl ToSql for PenaltyType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::PointDeduction => "point_deduction",
+            Self::TimeAddition => "time_addition",
+            Self::Disqualification => "disqualification",
+        }
+        .into())
+    }
+}
+impl FromSql for PenaltyType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("point_deduction") => Ok(Self::PointDeduction),
+            Ok("time_addition") => Ok(Self::TimeAddition),
+            Ok("disqualification") => Ok(Self::Disqualification),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Reason code that penalties are given for
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PenaltyCode {
+    pub id: Option<Uuid>,
+    pub code: String,
+    pub description: String,
+    pub enabled: bool,
+}
+impl PenaltyCode {
+    pub const TABLE_NAME: &'static str = "penalty_codes";
+
+    pub const COLUMNS_SQL: &'static str = "\"id\", \"code\", \"description\", \"enabled\"";
+
+    pub fn from_row(row: &Row) -> Self {
+        Self {
+            id: row
+                .get("id")
+                .expect("Failed to get value from database row"),
+            code: row
+                .get("code")
+                .expect("Failed to get value from database row"),
+            description: row
+                .get("description")
+                .expect("Failed to get value from database row"),
+            enabled: row
+                .get("enabled")
+                .expect("Failed to get value from database row"),
+        }
+    }
+}
+
+/// Penalty given to a team or group in a competition event
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Penalty {
+    pub id: Option<Uuid>,
+    pub competition_event_id: Uuid,
+    pub score_type: ScoreType,
+    pub subject_id: Uuid,
+    pub penalty_code_id: Uuid,
+    pub penalty_type: PenaltyType,
+    /// Points deducted or seconds added, not used for disqualifications
+    pub value: Option<Decimal>,
+    pub notes: Option<String>,
+    /// User who gave the penalty
+    #[serde(default)]
+    pub created_by: Option<Uuid>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+impl Penalty {
+    pub const TABLE_NAME: &'static str = "penalties";
+
+    pub const COLUMNS_SQL: &'static str =
+        "\"id\", \"competition_event_id\", \"score_type\", \"subject_id\", \"penalty_code_id\", \"penalty_type\", \"value\", \"notes\", \"created_by\", \"created_at\"";
+
+    pub fn from_row(row: &Row) -> Self {
+        Self {
+            id: row
+                .get("id")
+                .expect("Failed to get value from database row"),
+            competition_event_id: row
+                .get("competition_event_id")
+                .expect("Failed to get value from database row"),
+            score_type: row
+                .get("score_type")
+                .expect("Failed to get value from database row"),
+            subject_id: row
+                .get("subject_id")
+                .expect("Failed to get value from database row"),
+            penalty_code_id: row
+                .get("penalty_code_id")
+                .expect("Failed to get value from database row"),
+            penalty_type: row
+                .get("penalty_type")
+                .expect("Failed to get value from database row"),
+            value: row
+                .get::<_, Option<String>>("value")
+                .expect("Failed to get value from database row")
+                .map(|value| {
+                    value
+                        .parse()
+                        .expect("Failed to parse decimal from database row")
+                }),
+            notes: row
+                .get("notes")
+                .expect("Failed to get value from database row"),
+            created_by: row
+                .get("created_by")
+                .expect("Failed to get value from database row"),
+            created_at: row
+                .get("created_at")
+                .expect("Failed to get value from database row"),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Row, ToSql,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtestStatus {
+    #[default]
+    Open,
+    Upheld,
+    Denied,
+}
+impl ToSql for ProtestStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::Open => "open",
+            Self::Upheld => "upheld",
+            Self::Denied => "denied",
+        }
+        .into())
+    }
+}
+impl FromSql for ProtestStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("open") => Ok(Self::Open),
+            Ok("upheld") => Ok(Self::Upheld),
+            Ok("denied") => Ok(Self::Denied),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Protest against a score, or against the results of a competition event as a whole if there is no score
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Protest {
+    pub id: Option<Uuid>,
+    pub competition_event_id: Uuid,
+    /// Group or team score, which are stored in separate columns so they can reference their tables
+    pub score_id: Option<Uuid>,
+    pub reason: String,
+    /// User who filed the protest
+    #[serde(default)]
+    pub filed_by: Option<Uuid>,
+    #[serde(default)]
+    pub filed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub status: ProtestStatus,
+    #[serde(default)]
+    pub resolution_notes: Option<String>,
+    /// User who upheld or denied the protest
+    #[serde(default)]
+    pub resolved_by: Option<Uuid>,
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+impl Protest {
+    pub const TABLE_NAME: &'static str = "protests";
+
+    pub const COLUMNS_SQL: &'static str =
+        "\"id\", \"competition_event_id\", \"group_score_id\", \"team_score_id\", \"reason\", \"filed_by\", \"filed_at\", \"status\", \"resolution_notes\", \"resolved_by\", \"resolved_at\"";
+
+    pub fn from_row(row: &Row) -> Self {
+        Self {
+            id: row
+                .get("id")
+                .expect("Failed to get value from database row"),
+            competition_event_id: row
+                .get("competition_event_id")
+                .expect("Failed to get value from database row"),
+            score_id: row
+                .get::<_, Option<Uuid>>("group_score_id")
+                .expect("Failed to get value from database row")
+                .or(row
+                    .get("team_score_id")
+                    .expect("Failed to get value from database row")),
+            reason: row
+                .get("reason")
+                .expect("Failed to get value from database row"),
+            filed_by: row
+                .get("filed_by")
+                .expect("Failed to get value from database row"),
+            filed_at: row
+                .get("filed_at")
+                .expect("Failed to get value from database row"),
+            status: row
+                .get("status")
+                .expect("Failed to get value from database row"),
+            resolution_notes: row
+                .get("resolution_notes")
+                .expect("Failed to get value from database row"),
+            resolved_by: row
+                .get("resolved_by")
+                .expect("Failed to get value from database row"),
+            resolved_at: row
+                .get("resolved_at")
+                .expect("Failed to get value from database row"),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    ToSql,
+};
+use serde::{Deserialize, Serialize};
+
+/// How tied teams/groups are ranked
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RankingMethod {
+    /// Ties share the best rank and the following ranks are skipped (1, 2, 2, 4)
+    #[default]
+    Standard,
+    /// Ties share the best rank and no ranks are skipped (1, 2, 2, 3)
+    Dense,
+    /// Ties share the average of the ranks they cover (1, 2.5, 2.5, 4)
+    Fractional,
+}
+impl ToSql for RankingMethod {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::Standard => "standard",
+            Self::Dense => "dense",
+            Self::Fractional => "fractional",
+        }
+        .into())
+    }
+}
+impl FromSql for RankingMethod {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("standard") => Ok(Self::Standard),
+            Ok("dense") => Ok(Self::Dense),
+            Ok("fractional") => Ok(Self::Fractional),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Whether the highest or lowest score is ranked first
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RankingDirection {
+    #[default]
+    HighestWins,
+    LowestWins,
+}
+impl ToSql for RankingDirection {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::HighestWins => "highest_wins",
+            Self::LowestWins => "lowest_wins",
+        }
+        .into())
+    }
+}
+impl FromSql for RankingDirection {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("highest_wins") => Ok(Self::HighestWins),
+            Ok("lowest_wins") => Ok(Self::LowestWins),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
@@ -1,14 +1,58 @@
+use std::collections::BTreeMap;
+
 use rusqlite::Row;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize)]
+use crate::helpers::json::decode_json_string;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum ScoreFieldType {
     Number,
     Time,
     Boolean,
 }
+impl ScoreFieldType {
+    /// Checks that a value entered for a score field is of this type, returning an error message if it isn't
+    pub fn validate(&self, value: &JsonValue) -> Result<(), &'static str> {
+        match (self, value) {
+            (_, JsonValue::Null) => Err("is required"),
+            (_, JsonValue::String(value)) if value.trim().is_empty() => Err("is required"),
+            (Self::Number, JsonValue::Number(_)) => Ok(()),
+            (Self::Number, JsonValue::String(value))
+                if Decimal::from_str_exact(value.trim()).is_ok() =>
+            {
+                Ok(())
+            }
+            (Self::Number, _) => Err("must be a number"),
+            (Self::Time, JsonValue::Number(value))
+                if value.as_f64().is_some_and(|value| value >= 0.0) =>
+            {
+                Ok(())
+            }
+            (Self::Time, JsonValue::String(value)) if Self::is_time(value) => Ok(()),
+            (Self::Time, _) => Err("must be a time in seconds or in the format h:mm:ss.sss"),
+            (Self::Boolean, JsonValue::Bool(_)) => Ok(()),
+            (Self::Boolean, _) => Err("must be true or false"),
+        }
+    }
+
+    /// Checks if a string is a time in seconds, "m:ss.sss" or "h:mm:ss.sss"
+    fn is_time(value: &str) -> bool {
+        let parts: Vec<&str> = value.trim().split(':').map(str::trim).collect();
+        let Some((seconds, whole_parts)) = parts.split_last() else {
+            return false;
+        };
+
+        whole_parts.len() <= 2
+            && whole_parts
+                .iter()
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+            && Decimal::from_str_exact(seconds).is_ok_and(|seconds| !seconds.is_sign_negative())
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ScoreCalculator {
@@ -58,12 +102,19 @@ impl ScoreCalculator {
         }
     }
 
+    /// Gets the score fields by name, or `None` if the score calculator doesn't declare valid score fields
+    pub fn get_score_fields(&self) -> Option<BTreeMap<String, ScoreFieldType>> {
+        let score_fields = decode_json_string(self.score_fields.as_ref()?);
+        serde_json::from_value(score_fields).ok()
+    }
+
+    /// Score calculator used when none is set, which sums the points of the newest score of each event
     pub fn get_default() -> Self {
         ScoreCalculator {
             id: None,
             name: String::new(),
             description: String::new(),
-            script: String::from(""),
+            script: String::from(include_str!("../calculator/builtin/sum_of_points.lua")),
             default_config: json!({}),
             supports_seasons: true,
             supports_competitions: true,
@@ -72,3 +123,84 @@ impl ScoreCalculator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_are_required() {
+        for field_type in [
+            ScoreFieldType::Number,
+            ScoreFieldType::Time,
+            ScoreFieldType::Boolean,
+        ] {
+            assert_eq!(field_type.validate(&JsonValue::Null), Err("is required"));
+            assert_eq!(field_type.validate(&json!("  ")), Err("is required"));
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        let field_type = ScoreFieldType::Number;
+
+        assert!(field_type.validate(&json!(5)).is_ok());
+        assert!(field_type.validate(&json!(-2.5)).is_ok());
+        assert!(field_type.validate(&json!("1.25")).is_ok());
+        assert!(field_type.validate(&json!(" -3 ")).is_ok());
+        assert!(field_type.validate(&json!("1e3")).is_err());
+        assert!(field_type.validate(&json!("abc")).is_err());
+        assert!(field_type.validate(&json!(true)).is_err());
+        assert!(field_type.validate(&json!([1])).is_err());
+    }
+
+    #[test]
+    fn times() {
+        let field_type = ScoreFieldType::Time;
+
+        assert!(field_type.validate(&json!(0)).is_ok());
+        assert!(field_type.validate(&json!(61.5)).is_ok());
+        assert!(field_type.validate(&json!(-1)).is_err());
+        assert!(field_type.validate(&json!("75.25")).is_ok());
+        assert!(field_type.validate(&json!("1:15.25")).is_ok());
+        assert!(field_type.validate(&json!("1:01:15.250")).is_ok());
+        assert!(field_type.validate(&json!(" 1 : 15 ")).is_ok());
+        assert!(field_type.validate(&json!("1:2:3:4")).is_err());
+        assert!(field_type.validate(&json!(":15")).is_err());
+        assert!(field_type.validate(&json!("1.5:15")).is_err());
+        assert!(field_type.validate(&json!("1:-15")).is_err());
+        assert!(field_type.validate(&json!("1:xx")).is_err());
+        assert!(field_type.validate(&json!(false)).is_err());
+    }
+
+    #[test]
+    fn booleans() {
+        let field_type = ScoreFieldType::Boolean;
+
+        assert!(field_type.validate(&json!(true)).is_ok());
+        assert!(field_type.validate(&json!(false)).is_ok());
+        assert_eq!(
+            field_type.validate(&json!("true")),
+            Err("must be true or false")
+        );
+        assert_eq!(field_type.validate(&json!(1)), Err("must be true or false"));
+    }
+
+    #[test]
+    fn score_fields_are_read_from_encoded_json() {
+        let mut score_calculator = ScoreCalculator::get_default();
+
+        score_calculator.score_fields = Some(json!(r#"{"Time": "Time", "Finished": "Boolean"}"#));
+        let score_fields = score_calculator.get_score_fields().unwrap();
+        assert_eq!(
+            score_fields.keys().collect::<Vec<&String>>(),
+            ["Finished", "Time"]
+        );
+
+        score_calculator.score_fields = Some(json!({"Points": "Unknown"}));
+        assert!(score_calculator.get_score_fields().is_none());
+
+        score_calculator.score_fields = None;
+        assert!(score_calculator.get_score_fields().is_none());
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ScoreType {
     Group,
     Team,
@@ -31,6 +31,35 @@ impl FromSql for ScoreType {
     }
 }
 
+/// Whether a score has been checked by a second user before it is used in results
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationState {
+    #[default]
+    Entered,
+    Verified,
+    Rejected,
+}
+impl ToSql for VerificationState {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Self::Entered => "entered",
+            Self::Verified => "verified",
+            Self::Rejected => "rejected",
+        }
+        .into())
+    }
+}
+impl FromSql for VerificationState {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str() {
+            Ok("entered") => Ok(Self::Entered),
+            Ok("verified") => Ok(Self::Verified),
+            Ok("rejected") => Ok(Self::Rejected),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Score {
     pub id: Option<Uuid>,
@@ -42,11 +71,42 @@ pub struct Score {
     pub valid: bool,
     pub disqualified: bool,
     pub notes: Option<String>,
+    /// Id of the score this is an earlier revision of, `None` for the current revision
+    #[serde(default)]
+    pub original_id: Option<Uuid>,
+    #[serde(default)]
+    pub revision: i64,
+    /// User who originally entered the score
+    #[serde(default)]
+    pub entered_by: Option<Uuid>,
+    #[serde(default)]
+    pub entered_at: Option<DateTime<Utc>>,
+    /// User who entered this revision
+    #[serde(default)]
+    pub modified_by: Option<Uuid>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub verification_state: VerificationState,
+    /// User who verified or rejected the score
+    #[serde(default)]
+    pub verified_by: Option<Uuid>,
+    #[serde(default)]
+    pub verified_at: Option<DateTime<Utc>>,
+    /// User who judged the score, defaults to the user who entered it
+    #[serde(default)]
+    pub judge_id: Option<Uuid>,
 }
 impl Score {
+    /// Selects the current revision of all group and team scores
     pub const UNION_SELECT: &'static str =
-        "SELECT \"id\", \"competition_event_id\", 'group' AS \"score_type\", \"group_participation_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\" FROM \"group_scores\"
-UNION ALL SELECT \"id\", \"competition_event_id\", 'team' AS \"score_type\", \"team_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\" FROM \"team_scores\"";
+        "SELECT \"id\", \"competition_event_id\", 'group' AS \"score_type\", \"group_participation_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"group_scores\" WHERE \"original_id\" IS NULL
+UNION ALL SELECT \"id\", \"competition_event_id\", 'team' AS \"score_type\", \"team_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"team_scores\" WHERE \"original_id\" IS NULL";
+
+    /// Selects all revisions of all group and team scores
+    pub const REVISIONS_UNION_SELECT: &'static str =
+        "SELECT \"id\", \"competition_event_id\", 'group' AS \"score_type\", \"group_participation_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"group_scores\"
+UNION ALL SELECT \"id\", \"competition_event_id\", 'team' AS \"score_type\", \"team_id\" AS \"subject_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"team_scores\"";
 
     pub fn from_row(row: &Row) -> Self {
         Self {
@@ -77,6 +137,36 @@ UNION ALL SELECT \"id\", \"competition_event_id\", 'team' AS \"score_type\", \"t
             notes: row
                 .get("notes")
                 .expect("Failed to get value from database row"),
+            original_id: row
+                .get("original_id")
+                .expect("Failed to get value from database row"),
+            revision: row
+                .get("revision")
+                .expect("Failed to get value from database row"),
+            entered_by: row
+                .get("entered_by")
+                .expect("Failed to get value from database row"),
+            entered_at: row
+                .get("entered_at")
+                .expect("Failed to get value from database row"),
+            modified_by: row
+                .get("modified_by")
+                .expect("Failed to get value from database row"),
+            modified_at: row
+                .get("modified_at")
+                .expect("Failed to get value from database row"),
+            verification_state: row
+                .get("verification_state")
+                .expect("Failed to get value from database row"),
+            verified_by: row
+                .get("verified_by")
+                .expect("Failed to get value from database row"),
+            verified_at: row
+                .get("verified_at")
+                .expect("Failed to get value from database row"),
+            judge_id: row
+                .get("judge_id")
+                .expect("Failed to get value from database row"),
         }
     }
 }
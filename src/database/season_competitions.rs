@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::ranking::{RankingDirection, RankingMethod};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SeasonCompetition {
     pub id: Option<Uuid>,
@@ -12,12 +14,19 @@ pub struct SeasonCompetition {
     pub score_calculator: Option<Uuid>,
     pub calculator_config: JsonValue,
     pub enabled: bool,
+    #[serde(default)]
+    pub ranking_method: RankingMethod,
+    #[serde(default)]
+    pub ranking_direction: RankingDirection,
+    /// Scores in its competition events can't be changed while locked, only set by the lock and unlock actions
+    #[serde(default)]
+    pub locked: bool,
 }
 impl SeasonCompetition {
     pub const TABLE_NAME: &'static str = "season_competitions";
 
     pub const COLUMNS_SQL: &'static str =
-        "\"id\", \"season_id\", \"competition_id\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\"";
+        "\"id\", \"season_id\", \"competition_id\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\", \"ranking_method\", \"ranking_direction\", \"locked\"";
 
     pub fn from_row(row: &Row) -> Self {
         Self {
@@ -42,6 +51,15 @@ impl SeasonCompetition {
             enabled: row
                 .get("enabled")
                 .expect("Failed to get value from database row"),
+            ranking_method: row
+                .get("ranking_method")
+                .expect("Failed to get value from database row"),
+            ranking_direction: row
+                .get("ranking_direction")
+                .expect("Failed to get value from database row"),
+            locked: row
+                .get("locked")
+                .expect("Failed to get value from database row"),
         }
     }
 }
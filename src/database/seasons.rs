@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::ranking::{RankingDirection, RankingMethod};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Season {
     pub id: Option<Uuid>,
@@ -11,12 +13,19 @@ pub struct Season {
     pub score_calculator: Option<Uuid>,
     pub calculator_config: JsonValue,
     pub enabled: bool,
+    #[serde(default)]
+    pub ranking_method: RankingMethod,
+    #[serde(default)]
+    pub ranking_direction: RankingDirection,
+    /// Scores in its competition events can't be changed while locked, only set by the lock and unlock actions
+    #[serde(default)]
+    pub locked: bool,
 }
 impl Season {
     pub const TABLE_NAME: &'static str = "seasons";
 
     pub const COLUMNS_SQL: &'static str =
-        "\"id\", \"name\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\"";
+        "\"id\", \"name\", \"description\", \"score_calculator\", \"calculator_config\", \"enabled\", \"ranking_method\", \"ranking_direction\", \"locked\"";
 
     pub fn from_row(row: &Row) -> Self {
         Self {
@@ -38,6 +47,15 @@ impl Season {
             enabled: row
                 .get("enabled")
                 .expect("Failed to get value from database row"),
+            ranking_method: row
+                .get("ranking_method")
+                .expect("Failed to get value from database row"),
+            ranking_direction: row
+                .get("ranking_direction")
+                .expect("Failed to get value from database row"),
+            locked: row
+                .get("locked")
+                .expect("Failed to get value from database row"),
         }
     }
 }
@@ -14,6 +14,8 @@ impl UserPermission {
     pub const SCORE_ENTRY: i64 = 1 << 5;
     pub const REGISTRATION_VIEW: i64 = 1 << 6;
     pub const REGISTRATION_ENTRY: i64 = 1 << 7;
+    pub const SCORE_VERIFY: i64 = 1 << 8;
+    pub const LOCK_OVERRIDE: i64 = 1 << 9;
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -2,7 +2,7 @@ use std::{error::Error, fmt::Display};
 
 use axum::http::StatusCode;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct GenericError(u16);
 
 impl GenericError {
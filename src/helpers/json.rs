@@ -0,0 +1,18 @@
+use serde_json::Value as JsonValue;
+
+/// Decodes a JSON value stored as a JSON-encoded string (as the client does for config and score data).
+/// Values that aren't strings containing a JSON object or array are returned unchanged.
+pub fn decode_json_string(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::String(encoded) => match serde_json::from_str(encoded) {
+            Ok(decoded @ (JsonValue::Object(_) | JsonValue::Array(_))) => decoded,
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// Encodes a JSON value as a JSON-encoded string, the format the client uses for config and score data
+pub fn encode_json_string(value: &JsonValue) -> JsonValue {
+    JsonValue::String(value.to_string())
+}
@@ -1,3 +1,4 @@
 pub mod api_request_logging;
 pub mod auth_extractor;
 pub mod errors;
+pub mod json;
@@ -14,6 +14,7 @@ use axum::{
     Router,
 };
 
+use calculator::cache::ResultsCache;
 use config::AppConfig;
 use helpers::api_request_logging;
 use tokio::{net::TcpListener, signal};
@@ -28,12 +29,15 @@ use tower_http::{
 
 use database::Database;
 use services::{
-    audit::AuditService, auth::AuthService, competition_events::CompetitionEventsService,
-    competitions::CompetitionsService, events::EventsService,
+    audit::AuditService, auth::AuthService, changes::ChangesService,
+    competition_events::CompetitionEventsService, competitions::CompetitionsService,
+    completeness::CompletenessService, events::EventsService,
     group_participation::GroupParticipationsService, groups::GroupsService,
-    score_calculators::ScoreCalculatorsService, scores::ScoresService,
-    season_competitions::SeasonCompetitionsService, seasons::SeasonsService, teams::TeamsService,
-    users::UsersService,
+    judge_assignments::JudgeAssignmentsService, penalties::PenaltiesService,
+    penalty_codes::PenaltyCodesService, protests::ProtestsService,
+    score_calculators::ScoreCalculatorsService, score_import::ScoreImportService,
+    scores::ScoresService, season_competitions::SeasonCompetitionsService, seasons::SeasonsService,
+    teams::TeamsService, users::UsersService,
 };
 
 const CONFIG_FILE: &str = "./config.json";
@@ -43,6 +47,7 @@ pub struct AppState {
     pub database: Database,
     pub audit_service: AuditService,
     pub auth_service: AuthService,
+    pub changes_service: ChangesService,
     pub users_service: UsersService,
     pub score_calculators_service: ScoreCalculatorsService,
     pub seasons_service: SeasonsService,
@@ -51,9 +56,16 @@ pub struct AppState {
     pub events_service: EventsService,
     pub competition_events_service: CompetitionEventsService,
     pub groups_service: GroupsService,
+    pub judge_assignments_service: JudgeAssignmentsService,
     pub group_participations_service: GroupParticipationsService,
     pub teams_service: TeamsService,
     pub scores_service: ScoresService,
+    pub score_import_service: ScoreImportService,
+    pub protests_service: ProtestsService,
+    pub penalty_codes_service: PenaltyCodesService,
+    pub penalties_service: PenaltiesService,
+    pub completeness_service: CompletenessService,
+    pub results_cache: ResultsCache,
 }
 
 pub async fn ping(request: String) -> String {
@@ -75,17 +87,25 @@ pub async fn main() {
     let app_state = Arc::new(AppState {
         audit_service: AuditService::new(&database),
         auth_service: AuthService::new(&database, &config),
+        changes_service: ChangesService::new(&database),
         users_service: UsersService::new(&database, &config),
-        score_calculators_service: ScoreCalculatorsService::new(&database),
+        score_calculators_service: ScoreCalculatorsService::new(&database, &config),
         seasons_service: SeasonsService::new(&database),
         competitions_service: CompetitionsService::new(&database),
         season_competitions_service: SeasonCompetitionsService::new(&database),
         events_service: EventsService::new(&database),
         competition_events_service: CompetitionEventsService::new(&database),
         groups_service: GroupsService::new(&database),
+        judge_assignments_service: JudgeAssignmentsService::new(&database),
         group_participations_service: GroupParticipationsService::new(&database),
         teams_service: TeamsService::new(&database),
-        scores_service: ScoresService::new(&database),
+        scores_service: ScoresService::new(&database, &config),
+        score_import_service: ScoreImportService::new(&database, &config),
+        protests_service: ProtestsService::new(&database, &config),
+        penalty_codes_service: PenaltyCodesService::new(&database),
+        penalties_service: PenaltiesService::new(&database),
+        completeness_service: CompletenessService::new(&database, &config),
+        results_cache: ResultsCache::new(),
         database,
         config: config.clone(),
     });
@@ -0,0 +1,108 @@
+use rusqlite::{named_params, Connection};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::database::{
+    changes::{Change, ChangeAction},
+    Database,
+};
+
+/// Records entity changes for the change feed
+pub struct ChangesService {
+    db: Database,
+}
+
+impl ChangesService {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            db: database.clone(),
+        }
+    }
+
+    /// Lists changes with a sequence number after the specified one, oldest first
+    pub fn list_after(&self, sequence: i64, limit: i64) -> Vec<Change> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"{}\" WHERE \"sequence\" > :sequence ORDER BY \"sequence\" LIMIT :limit;",
+                Change::COLUMNS_SQL,
+                Change::TABLE_NAME
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":sequence": sequence,
+                    ":limit": limit,
+                },
+                |row| Ok(Change::from_row(row)),
+            )
+            .expect("Error occurred getting changes from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    /// Records that an entity was created or updated, along with its new data.
+    /// This should use the connection and transaction the entity was changed in,
+    /// so the change is only recorded if the mutation is committed.
+    pub fn record<T: Serialize>(
+        &self,
+        db: &Connection,
+        entity: &str,
+        action: ChangeAction,
+        entity_id: Uuid,
+        data: &T,
+    ) {
+        let data = serde_json::to_value(data).expect("Error occurred serializing change data");
+        Self::insert(db, entity, action, entity_id, Some(data));
+    }
+
+    /// Records that an entity was deleted, using the connection and transaction it was deleted in
+    pub fn record_delete(&self, db: &Connection, entity: &str, entity_id: Uuid) {
+        Self::insert(db, entity, ChangeAction::Delete, entity_id, None);
+    }
+
+    /// Records that the rows of `table` referencing a deleted entity through `column` were deleted by a foreign key cascade.
+    /// This must be called before the referenced entity is deleted, using the same transaction.
+    pub fn record_cascaded_deletes(
+        &self,
+        db: &Connection,
+        entity: &str,
+        table: &str,
+        column: &str,
+        referenced_id: Uuid,
+    ) {
+        db.prepare_cached(&format!(
+            "INSERT INTO \"{}\" (\"entity\", \"action\", \"entity_id\") SELECT :entity, :action, \"id\" FROM \"{}\" WHERE \"{}\" = :referenced_id;",
+            Change::TABLE_NAME,
+            table,
+            column
+        ))
+        .unwrap()
+        .execute(named_params! {
+            ":entity": entity,
+            ":action": ChangeAction::Delete,
+            ":referenced_id": referenced_id,
+        })
+        .expect("Error occurred recording cascaded deletes");
+    }
+
+    fn insert(
+        db: &Connection,
+        entity: &str,
+        action: ChangeAction,
+        entity_id: Uuid,
+        data: Option<serde_json::Value>,
+    ) {
+        db.prepare_cached(&format!("INSERT INTO \"{}\" (\"entity\", \"action\", \"entity_id\", \"data\") VALUES (:entity, :action, :entity_id, :data);", Change::TABLE_NAME))
+            .unwrap()
+            .execute(named_params! {
+                ":entity": entity,
+                ":action": action,
+                ":entity_id": entity_id,
+                ":data": data,
+            })
+            .expect("Error occurred recording change");
+    }
+}
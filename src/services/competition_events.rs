@@ -1,24 +1,35 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{competition_events::CompetitionEvent, Database},
+    database::{
+        changes::ChangeAction, competition_events::CompetitionEvent,
+        judge_assignments::JudgeAssignment, penalties::Penalty, protests::Protest, Database,
+    },
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct CompetitionEventsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl CompetitionEventsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<CompetitionEvent> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<CompetitionEvent> {
         let result: Option<CompetitionEvent> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"competition_events\" WHERE \"id\" = :id;",
@@ -72,10 +83,13 @@ impl CompetitionEventsService {
     pub fn create(&self, competition_event: &CompetitionEvent) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
-                "INSERT INTO \"competition_events\" ({}) VALUES (:id, :season_competition_id, :event_id, :description, :score_calculator, :calculator_config, :enabled, :score_type);",
+                "INSERT INTO \"competition_events\" ({}) VALUES (:id, :season_competition_id, :event_id, :description, :score_calculator, :calculator_config, :enabled, :score_type, :ranking_method, :ranking_direction, :verified_scores_only, 0);",
                 CompetitionEvent::COLUMNS_SQL
             ))
             .unwrap()
@@ -88,10 +102,23 @@ impl CompetitionEventsService {
                 ":calculator_config": competition_event.calculator_config,
                 ":enabled": competition_event.enabled,
                 ":score_type": competition_event.score_type,
+                ":ranking_method": competition_event.ranking_method,
+                ":ranking_direction": competition_event.ranking_direction,
+                ":verified_scores_only": competition_event.verified_scores_only,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "competition_event",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -103,8 +130,11 @@ impl CompetitionEventsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"competition_events\" SET \"season_competition_id\" = :season_competition_id, \"event_id\" = :event_id, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled, \"score_type\" = :score_type WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"competition_events\" SET \"season_competition_id\" = :season_competition_id, \"event_id\" = :event_id, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled, \"score_type\" = :score_type, \"ranking_method\" = :ranking_method, \"ranking_direction\" = :ranking_direction, \"verified_scores_only\" = :verified_scores_only WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": competition_event.id,
@@ -115,23 +145,111 @@ impl CompetitionEventsService {
                 ":calculator_config": competition_event.calculator_config,
                 ":enabled": competition_event.enabled,
                 ":score_type": competition_event.score_type,
+                ":ranking_method": competition_event.ranking_method,
+                ":ranking_direction": competition_event.ranking_direction,
+                ":verified_scores_only": competition_event.verified_scores_only,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "competition_event",
+                ChangeAction::Update,
+                competition_event.id.unwrap(),
+                &Self::get_with(&transaction, competition_event.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(competition_event.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
 
+    /// Checks if a competition event is locked, either itself or through its season competition or season
+    pub fn is_locked(&self, id: Uuid) -> bool {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached("SELECT EXISTS (SELECT 1 FROM \"competition_events\" INNER JOIN \"season_competitions\" ON \"season_competitions\".\"id\" = \"competition_events\".\"season_competition_id\" INNER JOIN \"seasons\" ON \"seasons\".\"id\" = \"season_competitions\".\"season_id\" WHERE \"competition_events\".\"id\" = :id AND (\"competition_events\".\"locked\" OR \"season_competitions\".\"locked\" OR \"seasons\".\"locked\"));")
+            .unwrap()
+            .query_row(named_params! {":id": id}, |row| row.get(0))
+            .expect("Error occurred checking if competition event is locked in database");
+
+        result
+    }
+
+    /// Locks or unlocks the competition event, which prevents scores in it from being changed
+    pub fn set_locked(&self, id: Uuid, locked: bool) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(
+                "UPDATE \"competition_events\" SET \"locked\" = :locked WHERE \"id\" = :id;",
+            )
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":locked": locked,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "competition_event",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
     pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
         let Some(_existing) = self.get(id) else {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        // judge assignments, penalties and protests for the competition event are deleted along with it
+        self.changes_service.record_cascaded_deletes(
+            &transaction,
+            "judge_assignment",
+            JudgeAssignment::TABLE_NAME,
+            "competition_event_id",
+            id,
+        );
+        self.changes_service.record_cascaded_deletes(
+            &transaction,
+            "penalty",
+            Penalty::TABLE_NAME,
+            "competition_event_id",
+            id,
+        );
+        self.changes_service.record_cascaded_deletes(
+            &transaction,
+            "protest",
+            Protest::TABLE_NAME,
+            "competition_event_id",
+            id,
+        );
+        let success = transaction
             .prepare_cached("DELETE FROM \"competition_events\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -140,6 +258,11 @@ impl CompetitionEventsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "competition_event", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -1,24 +1,32 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{competitions::Competition, Database},
+    database::{changes::ChangeAction, competitions::Competition, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct CompetitionsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl CompetitionsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<Competition> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Competition> {
         let result: Option<Competition> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"competitions\" WHERE \"id\" = :id;",
@@ -53,8 +61,11 @@ impl CompetitionsService {
     pub fn create(&self, competition: &Competition) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
                 "INSERT INTO \"competitions\" ({}) VALUES (:id, :name, :description, :enabled);",
                 Competition::COLUMNS_SQL
@@ -69,6 +80,16 @@ impl CompetitionsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "competition",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -80,8 +101,11 @@ impl CompetitionsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"competitions\" SET \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"competitions\" SET \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": competition.id,
@@ -92,6 +116,16 @@ impl CompetitionsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "competition",
+                ChangeAction::Update,
+                competition.id.unwrap(),
+                &Self::get_with(&transaction, competition.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(competition.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -103,8 +137,11 @@ impl CompetitionsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"competitions\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -113,6 +150,11 @@ impl CompetitionsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "competition", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::AppConfig,
+    database::{
+        competition_events::CompetitionEvent,
+        penalties::PenaltyType,
+        scores::{Score, ScoreType, VerificationState},
+        Database,
+    },
+    helpers::json::decode_json_string,
+};
+
+use super::{
+    competition_events::CompetitionEventsService, events::EventsService,
+    group_participation::GroupParticipationsService, groups::GroupsService,
+    penalties::PenaltiesService, scores::ScoresService,
+    season_competitions::SeasonCompetitionsService, teams::TeamsService,
+};
+
+/// Scoring state of a team/group in a competition event
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompletenessEntry {
+    pub competition_event_id: Uuid,
+    pub event_name: String,
+    pub score_type: ScoreType,
+    pub subject_id: Uuid,
+    pub group_name: String,
+    /// Empty for groups
+    pub team_name: String,
+    /// Valid scores that haven't been rejected
+    pub score_ids: Vec<Uuid>,
+    /// No valid score has been entered
+    pub missing: bool,
+    /// A judge has multiple valid scores with different score data
+    pub conflicting: bool,
+    /// A valid score is flagged as disqualified or there is a disqualification penalty
+    pub disqualified: bool,
+}
+
+/// Every enabled competition event crossed with every enabled team/group participation in a season or season competition
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompletenessReport {
+    pub entries: Vec<CompletenessEntry>,
+    pub missing: usize,
+    pub conflicting: usize,
+    pub disqualified: usize,
+}
+
+/// Enabled team/group participation in a season
+struct Subject {
+    score_type: ScoreType,
+    id: Uuid,
+    group_name: String,
+    team_name: String,
+}
+
+pub struct CompletenessService {
+    competition_events_service: CompetitionEventsService,
+    events_service: EventsService,
+    group_participations_service: GroupParticipationsService,
+    groups_service: GroupsService,
+    penalties_service: PenaltiesService,
+    scores_service: ScoresService,
+    season_competitions_service: SeasonCompetitionsService,
+    teams_service: TeamsService,
+}
+
+impl CompletenessService {
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
+        Self {
+            competition_events_service: CompetitionEventsService::new(database),
+            events_service: EventsService::new(database),
+            group_participations_service: GroupParticipationsService::new(database),
+            groups_service: GroupsService::new(database),
+            penalties_service: PenaltiesService::new(database),
+            scores_service: ScoresService::new(database, config),
+            season_competitions_service: SeasonCompetitionsService::new(database),
+            teams_service: TeamsService::new(database),
+        }
+    }
+
+    /// Gets the completeness of all enabled season competitions in a season
+    pub fn report_for_season(&self, season_id: Uuid) -> CompletenessReport {
+        let subjects = self.list_subjects(season_id);
+
+        let entries = self
+            .season_competitions_service
+            .list_in_season(season_id)
+            .into_iter()
+            .filter(|season_competition| season_competition.enabled)
+            .flat_map(|season_competition| {
+                self.competition_events_service
+                    .list_in_season_competition(season_competition.id.unwrap())
+            })
+            .flat_map(|competition_event| self.list_entries(&competition_event, &subjects))
+            .collect();
+
+        Self::summarize(entries)
+    }
+
+    /// Gets the completeness of a season competition, which is empty if the season competition is disabled
+    pub fn report_for_season_competition(&self, season_competition_id: Uuid) -> CompletenessReport {
+        let season_competition = self
+            .season_competitions_service
+            .get(season_competition_id)
+            .expect("Error occurred getting season competition");
+        let subjects = self.list_subjects(season_competition.season_id);
+
+        let entries = if season_competition.enabled {
+            self.competition_events_service
+                .list_in_season_competition(season_competition_id)
+                .into_iter()
+                .flat_map(|competition_event| self.list_entries(&competition_event, &subjects))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self::summarize(entries)
+    }
+
+    /// Gets an entry for each team/group of the competition event's score type, or none if the competition event or its event is disabled
+    fn list_entries(
+        &self,
+        competition_event: &CompetitionEvent,
+        subjects: &[Subject],
+    ) -> Vec<CompletenessEntry> {
+        let competition_event_id = competition_event.id.unwrap();
+        let Some(event) = self
+            .events_service
+            .get(competition_event.event_id)
+            .filter(|event| event.enabled && competition_event.enabled)
+        else {
+            return Vec::new();
+        };
+
+        let scores: Vec<Score> = self
+            .scores_service
+            .list_in_competition_event(competition_event_id)
+            .into_iter()
+            .filter(|score| {
+                score.valid && !matches!(score.verification_state, VerificationState::Rejected)
+            })
+            .collect();
+        let penalties = self
+            .penalties_service
+            .list_in_competition_event(competition_event_id);
+
+        subjects
+            .iter()
+            .filter(|subject| subject.score_type == competition_event.score_type)
+            .map(|subject| {
+                let subject_scores: Vec<&Score> = scores
+                    .iter()
+                    .filter(|score| score.subject_id == subject.id)
+                    .collect();
+
+                // scores from different judges are expected to differ, so only each judge's scores are compared
+                let mut judge_scores: HashMap<Option<Uuid>, Vec<&Score>> = HashMap::new();
+                for score in &subject_scores {
+                    judge_scores
+                        .entry(score.judge_id.or(score.entered_by))
+                        .or_default()
+                        .push(score);
+                }
+                let conflicting = judge_scores.values().any(|scores| {
+                    scores.iter().any(|score| {
+                        score.disqualified != scores[0].disqualified
+                            || decode_json_string(&score.score_data)
+                                != decode_json_string(&scores[0].score_data)
+                    })
+                });
+                let disqualified = subject_scores.iter().any(|score| score.disqualified)
+                    || penalties.iter().any(|penalty| {
+                        penalty.subject_id == subject.id
+                            && penalty.penalty_type == PenaltyType::Disqualification
+                    });
+
+                CompletenessEntry {
+                    competition_event_id,
+                    event_name: event.name.clone(),
+                    score_type: subject.score_type,
+                    subject_id: subject.id,
+                    group_name: subject.group_name.clone(),
+                    team_name: subject.team_name.clone(),
+                    score_ids: subject_scores
+                        .iter()
+                        .map(|score| score.id.unwrap())
+                        .collect(),
+                    missing: subject_scores.is_empty(),
+                    conflicting,
+                    disqualified,
+                }
+            })
+            .collect()
+    }
+
+    /// Gets all enabled group participations and their enabled teams in a season, sorted by name
+    fn list_subjects(&self, season_id: Uuid) -> Vec<Subject> {
+        let mut subjects = Vec::new();
+
+        for group_participation in self
+            .group_participations_service
+            .list_in_season(season_id)
+            .into_iter()
+            .filter(|group_participation| group_participation.enabled)
+        {
+            let group_participation_id = group_participation.id.unwrap();
+            let Some(group) = self
+                .groups_service
+                .get(group_participation.group_id)
+                .filter(|group| group.enabled)
+            else {
+                continue;
+            };
+
+            subjects.push(Subject {
+                score_type: ScoreType::Group,
+                id: group_participation_id,
+                group_name: group.name.clone(),
+                team_name: String::new(),
+            });
+
+            for team in self
+                .teams_service
+                .list_in_group_participation(group_participation_id)
+                .into_iter()
+                .filter(|team| team.enabled)
+            {
+                subjects.push(Subject {
+                    score_type: ScoreType::Team,
+                    id: team.id.unwrap(),
+                    group_name: group.name.clone(),
+                    team_name: team.name,
+                });
+            }
+        }
+
+        subjects.sort_by(|a, b| {
+            a.group_name
+                .cmp(&b.group_name)
+                .then_with(|| a.team_name.cmp(&b.team_name))
+        });
+
+        subjects
+    }
+
+    fn summarize(entries: Vec<CompletenessEntry>) -> CompletenessReport {
+        CompletenessReport {
+            missing: entries.iter().filter(|entry| entry.missing).count(),
+            conflicting: entries.iter().filter(|entry| entry.conflicting).count(),
+            disqualified: entries.iter().filter(|entry| entry.disqualified).count(),
+            entries,
+        }
+    }
+}
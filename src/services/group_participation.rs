@@ -1,24 +1,32 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{group_participation::GroupParticipation, Database},
+    database::{changes::ChangeAction, group_participation::GroupParticipation, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct GroupParticipationsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl GroupParticipationsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<GroupParticipation> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<GroupParticipation> {
         let result: Option<GroupParticipation> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"group_participation\" WHERE \"id\" = :id;",
@@ -77,8 +85,11 @@ impl GroupParticipationsService {
     pub fn create(&self, group_participation: &GroupParticipation) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
                 "INSERT INTO \"group_participation\" ({}) VALUES (:id, :group_id, :season_id, :description, :enabled);",
                 GroupParticipation::COLUMNS_SQL
@@ -94,6 +105,16 @@ impl GroupParticipationsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "group_participation",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -105,8 +126,11 @@ impl GroupParticipationsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"group_participation\" SET \"group_id\" = :group_id, \"season_id\" = :season_id, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"group_participation\" SET \"group_id\" = :group_id, \"season_id\" = :season_id, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": group_participation.id,
@@ -118,6 +142,16 @@ impl GroupParticipationsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "group_participation",
+                ChangeAction::Update,
+                group_participation.id.unwrap(),
+                &Self::get_with(&transaction, group_participation.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(group_participation.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -129,8 +163,11 @@ impl GroupParticipationsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"group_participation\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -139,6 +176,11 @@ impl GroupParticipationsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "group_participation", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
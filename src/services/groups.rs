@@ -1,24 +1,32 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{groups::Group, Database},
+    database::{changes::ChangeAction, groups::Group, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct GroupsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl GroupsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<Group> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Group> {
         let result: Option<Group> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"groups\" WHERE \"id\" = :id;",
@@ -48,8 +56,11 @@ impl GroupsService {
     pub fn create(&self, group: &Group) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
                 "INSERT INTO \"groups\" ({}) VALUES (:id, :name, :description, :enabled);",
                 Group::COLUMNS_SQL
@@ -64,6 +75,16 @@ impl GroupsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "group",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -75,8 +96,11 @@ impl GroupsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"groups\" SET \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"groups\" SET \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": group.id,
@@ -87,6 +111,16 @@ impl GroupsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "group",
+                ChangeAction::Update,
+                group.id.unwrap(),
+                &Self::get_with(&transaction, group.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(group.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -98,8 +132,11 @@ impl GroupsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"groups\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -108,6 +145,11 @@ impl GroupsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "group", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -0,0 +1,216 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        changes::ChangeAction,
+        competition_events::CompetitionEvent,
+        judge_assignments::JudgeAssignment,
+        users::{DbUser, UserPermission},
+        Database,
+    },
+    helpers::errors::GenericError,
+};
+
+use super::changes::ChangesService;
+
+pub struct JudgeAssignmentsService {
+    db: Database,
+    changes_service: ChangesService,
+}
+
+impl JudgeAssignmentsService {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            db: database.clone(),
+            changes_service: ChangesService::new(database),
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<JudgeAssignment> {
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<JudgeAssignment> {
+        let result: Option<JudgeAssignment> = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"judge_assignments\" WHERE \"id\" = :id;",
+                JudgeAssignment::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_row(named_params! {":id": id}, |row| {
+                Ok(JudgeAssignment::from_row(row))
+            })
+            .optional()
+            .expect("Error occurred getting judge assignment by id from database");
+
+        result
+    }
+
+    pub fn list(&self) -> Vec<JudgeAssignment> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"judge_assignments\";",
+                JudgeAssignment::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(named_params! {}, |row| Ok(JudgeAssignment::from_row(row)))
+            .expect("Error occurred getting all judge assignments from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    /// Lists the competition events a user is assigned to, either directly or through their season competition
+    pub fn list_assigned_competition_events(&self, user_id: Uuid) -> Vec<CompetitionEvent> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"competition_events\" WHERE \"id\" IN (SELECT \"competition_event_id\" FROM \"judge_assignments\" WHERE \"user_id\" = :user_id) OR \"season_competition_id\" IN (SELECT \"season_competition_id\" FROM \"judge_assignments\" WHERE \"user_id\" = :user_id);",
+                CompetitionEvent::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":user_id": user_id,
+                },
+                |row| Ok(CompetitionEvent::from_row(row)),
+            )
+            .expect("Error occurred getting assigned competition events from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    /// Checks if a user is assigned to a competition event, either directly or through its season competition
+    pub fn is_assigned(&self, user_id: Uuid, competition_event_id: Uuid) -> bool {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached("SELECT EXISTS (SELECT 1 FROM \"competition_events\" WHERE \"id\" = :competition_event_id AND (\"id\" IN (SELECT \"competition_event_id\" FROM \"judge_assignments\" WHERE \"user_id\" = :user_id) OR \"season_competition_id\" IN (SELECT \"season_competition_id\" FROM \"judge_assignments\" WHERE \"user_id\" = :user_id)));")
+            .unwrap()
+            .query_row(
+                named_params! {
+                    ":user_id": user_id,
+                    ":competition_event_id": competition_event_id,
+                },
+                |row| row.get(0),
+            )
+            .expect("Error occurred checking judge assignment in database");
+
+        result
+    }
+
+    /// Checks if a user can enter scores for a competition event.
+    /// Setup administrators can enter scores for any competition event, other users must be assigned to it.
+    pub fn can_enter_scores(&self, user: &DbUser, competition_event_id: Uuid) -> bool {
+        user.permissions & UserPermission::SETUP_ADMIN != 0
+            || self.is_assigned(user.id, competition_event_id)
+    }
+
+    pub fn create(&self, judge_assignment: &JudgeAssignment) -> Result<Uuid, GenericError> {
+        let id = Uuid::new_v4();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(&format!(
+                "INSERT INTO \"judge_assignments\" ({}) VALUES (:id, :user_id, :competition_event_id, :season_competition_id);",
+                JudgeAssignment::COLUMNS_SQL
+            ))
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":user_id": judge_assignment.user_id,
+                ":competition_event_id": judge_assignment.competition_event_id,
+                ":season_competition_id": judge_assignment.season_competition_id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "judge_assignment",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn update(&self, judge_assignment: &JudgeAssignment) -> Result<Uuid, GenericError> {
+        let Some(_existing) = self.get(judge_assignment.id.unwrap()) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"judge_assignments\" SET \"user_id\" = :user_id, \"competition_event_id\" = :competition_event_id, \"season_competition_id\" = :season_competition_id WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": judge_assignment.id,
+                ":user_id": judge_assignment.user_id,
+                ":competition_event_id": judge_assignment.competition_event_id,
+                ":season_competition_id": judge_assignment.season_competition_id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "judge_assignment",
+                ChangeAction::Update,
+                judge_assignment.id.unwrap(),
+                &Self::get_with(&transaction, judge_assignment.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(judge_assignment.id.unwrap())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("DELETE FROM \"judge_assignments\" WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service
+                .record_delete(&transaction, "judge_assignment", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+}
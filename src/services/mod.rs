@@ -1,11 +1,18 @@
 pub mod audit;
 pub mod auth;
+pub mod changes;
 pub mod competition_events;
 pub mod competitions;
+pub mod completeness;
 pub mod events;
 pub mod group_participation;
 pub mod groups;
+pub mod judge_assignments;
+pub mod penalties;
+pub mod penalty_codes;
+pub mod protests;
 pub mod score_calculators;
+pub mod score_import;
 pub mod scores;
 pub mod season_competitions;
 pub mod seasons;
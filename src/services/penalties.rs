@@ -0,0 +1,302 @@
+use chrono::Utc;
+use rusqlite::{named_params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        changes::ChangeAction,
+        penalties::{Penalty, PenaltyType},
+        scores::ScoreType,
+        users::{DbUser, UserPermission},
+        Database,
+    },
+    helpers::errors::GenericError,
+};
+
+use super::{
+    changes::ChangesService, competition_events::CompetitionEventsService,
+    group_participation::GroupParticipationsService, judge_assignments::JudgeAssignmentsService,
+    penalty_codes::PenaltyCodesService, season_competitions::SeasonCompetitionsService,
+    teams::TeamsService,
+};
+
+pub struct PenaltiesService {
+    db: Database,
+    changes_service: ChangesService,
+    competition_events_service: CompetitionEventsService,
+    group_participations_service: GroupParticipationsService,
+    judge_assignments_service: JudgeAssignmentsService,
+    penalty_codes_service: PenaltyCodesService,
+    season_competitions_service: SeasonCompetitionsService,
+    teams_service: TeamsService,
+}
+
+impl PenaltiesService {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            db: database.clone(),
+            changes_service: ChangesService::new(database),
+            competition_events_service: CompetitionEventsService::new(database),
+            group_participations_service: GroupParticipationsService::new(database),
+            judge_assignments_service: JudgeAssignmentsService::new(database),
+            penalty_codes_service: PenaltyCodesService::new(database),
+            season_competitions_service: SeasonCompetitionsService::new(database),
+            teams_service: TeamsService::new(database),
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Penalty> {
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Penalty> {
+        let result: Option<Penalty> = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"penalties\" WHERE \"id\" = :id;",
+                Penalty::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_row(named_params! {":id": id}, |row| Ok(Penalty::from_row(row)))
+            .optional()
+            .expect("Error occurred getting penalty by id from database");
+
+        result
+    }
+
+    pub fn list(&self) -> Vec<Penalty> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"penalties\";",
+                Penalty::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(named_params! {}, |row| Ok(Penalty::from_row(row)))
+            .expect("Error occurred getting all penalties from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    pub fn list_in_competition_event(&self, competition_event_id: Uuid) -> Vec<Penalty> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"penalties\" WHERE \"competition_event_id\" = :competition_event_id ORDER BY \"created_at\";",
+                Penalty::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":competition_event_id": competition_event_id,
+                },
+                |row| Ok(Penalty::from_row(row)),
+            )
+            .expect("Error occurred getting penalties in competition event from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    /// Checks that the penalty is for a team/group of the competition event's score type in the same season,
+    /// and that point deductions and time additions have a positive value
+    fn is_valid(&self, penalty: &Penalty) -> bool {
+        let Some(competition_event) = self
+            .competition_events_service
+            .get(penalty.competition_event_id)
+        else {
+            return false;
+        };
+        let Some(season_competition) = self
+            .season_competitions_service
+            .get(competition_event.season_competition_id)
+        else {
+            return false;
+        };
+
+        let group_participation_id = match penalty.score_type {
+            ScoreType::Group => Some(penalty.subject_id),
+            ScoreType::Team => self
+                .teams_service
+                .get(penalty.subject_id)
+                .map(|team| team.group_participation_id),
+        };
+        let in_season = group_participation_id
+            .and_then(|group_participation_id| {
+                self.group_participations_service
+                    .get(group_participation_id)
+            })
+            .is_some_and(|group_participation| {
+                group_participation.season_id == season_competition.season_id
+            });
+
+        let valid_value = match penalty.penalty_type {
+            PenaltyType::PointDeduction | PenaltyType::TimeAddition => penalty
+                .value
+                .is_some_and(|value| value.is_sign_positive() && !value.is_zero()),
+            PenaltyType::Disqualification => penalty.value.is_none(),
+        };
+
+        penalty.score_type == competition_event.score_type && in_season && valid_value
+    }
+
+    /// Checks if the user can give penalties in a competition event, which uses the same judge assignments and locks as scores
+    fn check_can_change(
+        &self,
+        competition_event_id: Uuid,
+        current_user: &DbUser,
+    ) -> Result<(), GenericError> {
+        if !self
+            .judge_assignments_service
+            .can_enter_scores(current_user, competition_event_id)
+        {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if current_user.permissions & UserPermission::LOCK_OVERRIDE == 0
+            && self
+                .competition_events_service
+                .is_locked(competition_event_id)
+        {
+            return Err(GenericError::CONFLICT);
+        }
+
+        Ok(())
+    }
+
+    pub fn create(&self, penalty: &Penalty, current_user: &DbUser) -> Result<Uuid, GenericError> {
+        self.check_can_change(penalty.competition_event_id, current_user)?;
+
+        if !self.is_valid(penalty)
+            || !self
+                .penalty_codes_service
+                .get(penalty.penalty_code_id)
+                .is_some_and(|penalty_code| penalty_code.enabled)
+        {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        let id = Uuid::new_v4();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(&format!(
+                "INSERT INTO \"penalties\" ({}) VALUES (:id, :competition_event_id, :score_type, :subject_id, :penalty_code_id, :penalty_type, :value, :notes, :created_by, :created_at);",
+                Penalty::COLUMNS_SQL
+            ))
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":competition_event_id": penalty.competition_event_id,
+                ":score_type": penalty.score_type,
+                ":subject_id": penalty.subject_id,
+                ":penalty_code_id": penalty.penalty_code_id,
+                ":penalty_type": penalty.penalty_type,
+                ":value": penalty.value.map(|value| value.to_string()),
+                ":notes": penalty.notes,
+                ":created_by": current_user.id,
+                ":created_at": Utc::now(),
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "penalty",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn update(&self, penalty: &Penalty, current_user: &DbUser) -> Result<Uuid, GenericError> {
+        let Some(existing) = self.get(penalty.id.unwrap()) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        self.check_can_change(existing.competition_event_id, current_user)?;
+        self.check_can_change(penalty.competition_event_id, current_user)?;
+
+        if !self.is_valid(penalty) {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"penalties\" SET \"competition_event_id\" = :competition_event_id, \"score_type\" = :score_type, \"subject_id\" = :subject_id, \"penalty_code_id\" = :penalty_code_id, \"penalty_type\" = :penalty_type, \"value\" = :value, \"notes\" = :notes WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": penalty.id,
+                ":competition_event_id": penalty.competition_event_id,
+                ":score_type": penalty.score_type,
+                ":subject_id": penalty.subject_id,
+                ":penalty_code_id": penalty.penalty_code_id,
+                ":penalty_type": penalty.penalty_type,
+                ":value": penalty.value.map(|value| value.to_string()),
+                ":notes": penalty.notes,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "penalty",
+                ChangeAction::Update,
+                penalty.id.unwrap(),
+                &Self::get_with(&transaction, penalty.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(penalty.id.unwrap())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn delete(&self, id: Uuid, current_user: &DbUser) -> Result<(), GenericError> {
+        let Some(existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        self.check_can_change(existing.competition_event_id, current_user)?;
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("DELETE FROM \"penalties\" WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service
+                .record_delete(&transaction, "penalty", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::{
+    database::{changes::ChangeAction, penalties::PenaltyCode, Database},
+    helpers::errors::GenericError,
+};
+
+use super::changes::ChangesService;
+
+pub struct PenaltyCodesService {
+    db: Database,
+    changes_service: ChangesService,
+}
+
+impl PenaltyCodesService {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            db: database.clone(),
+            changes_service: ChangesService::new(database),
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<PenaltyCode> {
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<PenaltyCode> {
+        let result: Option<PenaltyCode> = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"penalty_codes\" WHERE \"id\" = :id;",
+                PenaltyCode::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_row(named_params! {":id": id}, |row| {
+                Ok(PenaltyCode::from_row(row))
+            })
+            .optional()
+            .expect("Error occurred getting penalty code by id from database");
+
+        result
+    }
+
+    pub fn list(&self) -> Vec<PenaltyCode> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"penalty_codes\";",
+                PenaltyCode::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(named_params! {}, |row| Ok(PenaltyCode::from_row(row)))
+            .expect("Error occurred getting all penalty codes from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    pub fn create(&self, penalty_code: &PenaltyCode) -> Result<Uuid, GenericError> {
+        let id = Uuid::new_v4();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(&format!(
+                "INSERT INTO \"penalty_codes\" ({}) VALUES (:id, :code, :description, :enabled);",
+                PenaltyCode::COLUMNS_SQL
+            ))
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":code": penalty_code.code,
+                ":description": penalty_code.description,
+                ":enabled": penalty_code.enabled,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "penalty_code",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn update(&self, penalty_code: &PenaltyCode) -> Result<Uuid, GenericError> {
+        let Some(_existing) = self.get(penalty_code.id.unwrap()) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"penalty_codes\" SET \"code\" = :code, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": penalty_code.id,
+                ":code": penalty_code.code,
+                ":description": penalty_code.description,
+                ":enabled": penalty_code.enabled,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "penalty_code",
+                ChangeAction::Update,
+                penalty_code.id.unwrap(),
+                &Self::get_with(&transaction, penalty_code.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(penalty_code.id.unwrap())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    /// Deletes a penalty code, which fails if any penalties were given for it
+    pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("DELETE FROM \"penalty_codes\" WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service
+                .record_delete(&transaction, "penalty_code", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+}
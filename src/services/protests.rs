@@ -0,0 +1,292 @@
+use chrono::Utc;
+use rusqlite::{named_params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::{
+    config::AppConfig,
+    database::{
+        changes::ChangeAction,
+        protests::{Protest, ProtestStatus},
+        scores::ScoreType,
+        users::DbUser,
+        Database,
+    },
+    helpers::errors::GenericError,
+};
+
+use super::{changes::ChangesService, scores::ScoresService};
+
+pub struct ProtestsService {
+    db: Database,
+    changes_service: ChangesService,
+    scores_service: ScoresService,
+}
+
+impl ProtestsService {
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
+        Self {
+            db: database.clone(),
+            changes_service: ChangesService::new(database),
+            scores_service: ScoresService::new(database, config),
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Protest> {
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Protest> {
+        let result: Option<Protest> = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"protests\" WHERE \"id\" = :id;",
+                Protest::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_row(named_params! {":id": id}, |row| Ok(Protest::from_row(row)))
+            .optional()
+            .expect("Error occurred getting protest by id from database");
+
+        result
+    }
+
+    pub fn list(&self) -> Vec<Protest> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"protests\" ORDER BY \"filed_at\";",
+                Protest::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(named_params! {}, |row| Ok(Protest::from_row(row)))
+            .expect("Error occurred getting all protests from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    pub fn list_in_competition_event(&self, competition_event_id: Uuid) -> Vec<Protest> {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT {} FROM \"protests\" WHERE \"competition_event_id\" = :competition_event_id ORDER BY \"filed_at\";",
+                Protest::COLUMNS_SQL
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":competition_event_id": competition_event_id,
+                },
+                |row| Ok(Protest::from_row(row)),
+            )
+            .expect("Error occurred getting protests in competition event from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
+    /// Checks if a competition event has any open protests, either against its results or any of its scores
+    pub fn has_open_protests(&self, competition_event_id: Uuid) -> bool {
+        let db = self.db.get();
+        let result = db
+            .prepare_cached("SELECT EXISTS (SELECT 1 FROM \"protests\" WHERE \"competition_event_id\" = :competition_event_id AND \"status\" = :status);")
+            .unwrap()
+            .query_row(
+                named_params! {
+                    ":competition_event_id": competition_event_id,
+                    ":status": ProtestStatus::Open,
+                },
+                |row| row.get(0),
+            )
+            .expect("Error occurred checking for open protests in database");
+
+        result
+    }
+
+    /// Files a protest, which must be against a score in the protest's competition event if it is against a score
+    pub fn create(&self, protest: &Protest, current_user: &DbUser) -> Result<Uuid, GenericError> {
+        let score = match protest.score_id {
+            Some(score_id) => match self.scores_service.get(score_id) {
+                Some(score) if score.competition_event_id == protest.competition_event_id => {
+                    Some(score)
+                }
+                _ => return Err(GenericError::BAD_REQUEST),
+            },
+            None => None,
+        };
+        let score_id = |score_type: ScoreType| {
+            score
+                .as_ref()
+                .filter(|score| score.score_type == score_type)
+                .and_then(|score| score.id)
+        };
+
+        let id = Uuid::new_v4();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(&format!(
+                "INSERT INTO \"protests\" ({}) VALUES (:id, :competition_event_id, :group_score_id, :team_score_id, :reason, :filed_by, :filed_at, :status, NULL, NULL, NULL);",
+                Protest::COLUMNS_SQL
+            ))
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":competition_event_id": protest.competition_event_id,
+                ":group_score_id": score_id(ScoreType::Group),
+                ":team_score_id": score_id(ScoreType::Team),
+                ":reason": protest.reason,
+                ":filed_by": current_user.id,
+                ":filed_at": Utc::now(),
+                ":status": ProtestStatus::Open,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "protest",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    /// Updates the reason for a protest, which can only be done by the user who filed it while it is open
+    pub fn update(&self, protest: &Protest, current_user: &DbUser) -> Result<Uuid, GenericError> {
+        let Some(existing) = self.get(protest.id.unwrap()) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        if existing.filed_by != Some(current_user.id) {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if existing.status != ProtestStatus::Open {
+            return Err(GenericError::CONFLICT);
+        }
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("UPDATE \"protests\" SET \"reason\" = :reason WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": protest.id,
+                ":reason": protest.reason,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "protest",
+                ChangeAction::Update,
+                protest.id.unwrap(),
+                &Self::get_with(&transaction, protest.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(protest.id.unwrap())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    /// Upholds or denies an open protest
+    pub fn resolve(
+        &self,
+        id: Uuid,
+        status: ProtestStatus,
+        resolution_notes: Option<&str>,
+        current_user: &DbUser,
+    ) -> Result<Uuid, GenericError> {
+        let Some(existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        if status == ProtestStatus::Open {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        if existing.status != ProtestStatus::Open {
+            return Err(GenericError::CONFLICT);
+        }
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("UPDATE \"protests\" SET \"status\" = :status, \"resolution_notes\" = :resolution_notes, \"resolved_by\" = :resolved_by, \"resolved_at\" = :resolved_at WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":status": status,
+                ":resolution_notes": resolution_notes,
+                ":resolved_by": current_user.id,
+                ":resolved_at": Utc::now(),
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "protest",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("DELETE FROM \"protests\" WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service
+                .record_delete(&transaction, "protest", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+}
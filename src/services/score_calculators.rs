@@ -1,24 +1,39 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{score_calculators::ScoreCalculator, Database},
+    calculator::{
+        builtin::builtin_calculators,
+        validation::{self, ScriptError},
+    },
+    config::AppConfig,
+    database::{changes::ChangeAction, score_calculators::ScoreCalculator, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct ScoreCalculatorsService {
+    config: AppConfig,
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl ScoreCalculatorsService {
-    pub fn new(database: &Database) -> Self {
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
         Self {
+            config: config.clone(),
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<ScoreCalculator> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<ScoreCalculator> {
         let result = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"score_calculators\" WHERE \"id\" = :id;",
@@ -50,11 +65,23 @@ impl ScoreCalculatorsService {
         result
     }
 
+    /// Checks that the script compiles and defines the functions for each supported level
+    pub fn validate(&self, score_calculator: &ScoreCalculator) -> Result<(), ScriptError> {
+        validation::validate_script(&self.config, score_calculator)
+    }
+
     pub fn create(&self, score_calculator: &ScoreCalculator) -> Result<Uuid, GenericError> {
+        if self.validate(score_calculator).is_err() {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
                 "INSERT INTO \"score_calculators\" ({}) VALUES (:id, :name, :description, :script, :default_config, :supports_seasons, :supports_competitions, :supports_events, :score_fields);",
                 ScoreCalculator::COLUMNS_SQL
@@ -74,6 +101,16 @@ impl ScoreCalculatorsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "score_calculator",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -85,8 +122,15 @@ impl ScoreCalculatorsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"score_calculators\" SET \"name\" = :name, \"description\" = :description, \"script\" = :script, \"default_config\" = :default_config, \"supports_seasons\" = :supports_seasons, \"supports_competitions\" = :supports_competitions, \"supports_events\" = :supports_events, \"score_fields\" = :score_fields WHERE \"id\" = :id;")
+        if self.validate(score_calculator).is_err() {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"score_calculators\" SET \"name\" = :name, \"description\" = :description, \"script\" = :script, \"default_config\" = :default_config, \"supports_seasons\" = :supports_seasons, \"supports_competitions\" = :supports_competitions, \"supports_events\" = :supports_events, \"score_fields\" = :score_fields WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": score_calculator.id,
@@ -102,6 +146,16 @@ impl ScoreCalculatorsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "score_calculator",
+                ChangeAction::Update,
+                score_calculator.id.unwrap(),
+                &Self::get_with(&transaction, score_calculator.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(score_calculator.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -113,8 +167,11 @@ impl ScoreCalculatorsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"score_calculators\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -123,9 +180,38 @@ impl ScoreCalculatorsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "score_calculator", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
+
+    /// Creates the built-in score calculators, returning the ids of the ones that were created or replaced.
+    /// Existing score calculators with the same name as a built-in one are only replaced if `replace_existing` is set.
+    pub fn import_builtin(&self, replace_existing: bool) -> Result<Vec<Uuid>, GenericError> {
+        let existing = self.list();
+
+        let mut ids = Vec::new();
+        for builtin in builtin_calculators() {
+            match existing
+                .iter()
+                .find(|score_calculator| score_calculator.name == builtin.name)
+            {
+                Some(existing) if replace_existing => {
+                    let mut score_calculator = builtin.clone();
+                    score_calculator.id = existing.id;
+                    ids.push(self.update(&score_calculator)?);
+                }
+                Some(_) => {}
+                None => ids.push(self.create(&builtin)?),
+            }
+        }
+
+        Ok(ids)
+    }
 }
@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::{
+    config::AppConfig,
+    database::{
+        score_calculators::{ScoreCalculator, ScoreFieldType},
+        scores::{Score, ScoreType},
+        users::DbUser,
+        Database,
+    },
+    helpers::{errors::GenericError, json::encode_json_string},
+};
+
+use super::{
+    competition_events::CompetitionEventsService, group_participation::GroupParticipationsService,
+    groups::GroupsService, score_calculators::ScoreCalculatorsService, scores::ScoresService,
+    season_competitions::SeasonCompetitionsService, teams::TeamsService,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreImportStatus {
+    /// The team or group was found and the score is valid
+    Matched,
+    /// No team or group was found with the name
+    Unmatched,
+    /// The team or group was found, but the score isn't valid
+    Invalid,
+}
+
+/// A row of an imported CSV file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreImportRow {
+    /// Row number in the CSV file, not counting the header row
+    pub row: usize,
+    /// Team name for team scores or group name for group scores
+    pub name: String,
+    /// Group name of the team, if there is a group column
+    pub group_name: Option<String>,
+    pub status: ScoreImportStatus,
+    pub score: Option<Score>,
+    pub errors: Vec<String>,
+    pub field_errors: BTreeMap<String, String>,
+}
+
+/// Result of previewing or committing a CSV score import
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreImport {
+    pub competition_event_id: Uuid,
+    /// Problems with the file as a whole, such as missing or unknown columns
+    pub errors: Vec<String>,
+    pub rows: Vec<ScoreImportRow>,
+    pub matched: usize,
+    pub unmatched: usize,
+    pub invalid: usize,
+    /// IDs of the created scores, if the import was committed
+    pub created: Option<Vec<Uuid>>,
+}
+impl ScoreImport {
+    /// Checks if every row can be imported
+    pub fn is_importable(&self) -> bool {
+        self.errors.is_empty()
+            && !self.rows.is_empty()
+            && self
+                .rows
+                .iter()
+                .all(|row| row.status == ScoreImportStatus::Matched)
+    }
+}
+
+/// Imports scores for a competition event from CSV files with a team and/or group name column and a column for each score field
+pub struct ScoreImportService {
+    competition_events_service: CompetitionEventsService,
+    group_participations_service: GroupParticipationsService,
+    groups_service: GroupsService,
+    score_calculators_service: ScoreCalculatorsService,
+    scores_service: ScoresService,
+    season_competitions_service: SeasonCompetitionsService,
+    teams_service: TeamsService,
+}
+
+impl ScoreImportService {
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
+        Self {
+            competition_events_service: CompetitionEventsService::new(database),
+            group_participations_service: GroupParticipationsService::new(database),
+            groups_service: GroupsService::new(database),
+            score_calculators_service: ScoreCalculatorsService::new(database, config),
+            scores_service: ScoresService::new(database, config),
+            season_competitions_service: SeasonCompetitionsService::new(database),
+            teams_service: TeamsService::new(database),
+        }
+    }
+
+    /// Matches the rows of a CSV file to teams or groups in the competition event's season and validates their scores without creating them
+    pub fn preview(
+        &self,
+        competition_event_id: Uuid,
+        csv: &str,
+    ) -> Result<ScoreImport, GenericError> {
+        let Some(competition_event) = self.competition_events_service.get(competition_event_id)
+        else {
+            return Err(GenericError::NOT_FOUND);
+        };
+        let Some(season_competition) = self
+            .season_competitions_service
+            .get(competition_event.season_competition_id)
+        else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut import = ScoreImport {
+            competition_event_id,
+            errors: Vec::new(),
+            rows: Vec::new(),
+            matched: 0,
+            unmatched: 0,
+            invalid: 0,
+            created: None,
+        };
+
+        let (columns, rows) = match read_csv(csv) {
+            Ok(csv) => csv,
+            Err(error) => {
+                import.errors.push(error);
+                return Ok(import);
+            }
+        };
+
+        let score_fields = competition_event
+            .score_calculator
+            .and_then(|score_calculator| self.score_calculators_service.get(score_calculator))
+            .unwrap_or_else(ScoreCalculator::get_default)
+            .get_score_fields()
+            .unwrap_or_default();
+
+        // find the column of each score field and the name columns, ignoring case
+        let find_column = |name: &str| {
+            columns
+                .iter()
+                .position(|column| column.eq_ignore_ascii_case(name))
+        };
+        let team_column = find_column("team");
+        let group_column = find_column("group");
+        let notes_column = find_column("notes");
+        let field_columns: Vec<(&String, &ScoreFieldType, usize)> = score_fields
+            .iter()
+            .filter_map(|(name, field_type)| {
+                find_column(name).map(|column| (name, field_type, column))
+            })
+            .collect();
+
+        let name_column = match competition_event.score_type {
+            ScoreType::Group => group_column,
+            ScoreType::Team => team_column,
+        };
+        if name_column.is_none() {
+            import.errors.push(format!(
+                "CSV must have a {} column",
+                match competition_event.score_type {
+                    ScoreType::Group => "group",
+                    ScoreType::Team => "team",
+                }
+            ));
+        }
+        for name in score_fields.keys() {
+            if find_column(name).is_none() {
+                import.errors.push(format!(
+                    "CSV is missing a column for the score field {}",
+                    name
+                ));
+            }
+        }
+        for (index, column) in columns.iter().enumerate() {
+            if Some(index) != team_column
+                && Some(index) != group_column
+                && Some(index) != notes_column
+                && !field_columns.iter().any(|(_, _, x)| *x == index)
+            {
+                import
+                    .errors
+                    .push(format!("{} is not a score field", column));
+            }
+        }
+        let Some(name_column) = name_column else {
+            return Ok(import);
+        };
+
+        // teams and groups in the season, along with their group names
+        let group_participations: Vec<(Uuid, String)> = self
+            .group_participations_service
+            .list_in_season(season_competition.season_id)
+            .into_iter()
+            .map(|group_participation| {
+                let group_name = self
+                    .groups_service
+                    .get(group_participation.group_id)
+                    .map(|group| group.name)
+                    .unwrap_or_default();
+                (group_participation.id.unwrap(), group_name)
+            })
+            .collect();
+        let teams: Vec<(Uuid, String, String)> = match competition_event.score_type {
+            ScoreType::Group => Vec::new(),
+            ScoreType::Team => group_participations
+                .iter()
+                .flat_map(|(group_participation_id, group_name)| {
+                    self.teams_service
+                        .list_in_group_participation(*group_participation_id)
+                        .into_iter()
+                        .map(|team| (team.id.unwrap(), team.name, group_name.clone()))
+                })
+                .collect(),
+        };
+
+        for (index, row) in rows.iter().enumerate() {
+            let name = row[name_column].clone();
+            let group_name = match competition_event.score_type {
+                ScoreType::Group => None,
+                ScoreType::Team => group_column
+                    .map(|group_column| row[group_column].clone())
+                    .filter(|group_name| !group_name.trim().is_empty()),
+            };
+
+            let mut import_row = ScoreImportRow {
+                row: index + 1,
+                name: name.clone(),
+                group_name: group_name.clone(),
+                status: ScoreImportStatus::Unmatched,
+                score: None,
+                errors: Vec::new(),
+                field_errors: BTreeMap::new(),
+            };
+
+            let matches: Vec<Uuid> = match competition_event.score_type {
+                ScoreType::Group => group_participations
+                    .iter()
+                    .filter(|(_, group)| names_match(group, &name))
+                    .map(|(id, _)| *id)
+                    .collect(),
+                ScoreType::Team => teams
+                    .iter()
+                    .filter(|(_, team, group)| {
+                        team_matches(team, group, &name, group_name.as_deref())
+                    })
+                    .map(|(id, _, _)| *id)
+                    .collect(),
+            };
+
+            match matches[..] {
+                [] => {
+                    import_row.errors.push(format!(
+                        "No {} named {} in the competition event's season",
+                        match competition_event.score_type {
+                            ScoreType::Group => "group",
+                            ScoreType::Team => "team",
+                        },
+                        name.trim()
+                    ));
+                    import.unmatched += 1;
+                }
+                [subject_id] => {
+                    let score_data: JsonMap<String, JsonValue> = field_columns
+                        .iter()
+                        .map(|(field_name, field_type, column)| {
+                            ((*field_name).clone(), csv_value(field_type, &row[*column]))
+                        })
+                        .collect();
+                    let notes = notes_column
+                        .map(|notes_column| row[notes_column].trim().to_owned())
+                        .filter(|notes| !notes.is_empty());
+
+                    let score = Score {
+                        id: None,
+                        competition_event_id,
+                        score_type: competition_event.score_type,
+                        subject_id,
+                        score_data: encode_json_string(&JsonValue::Object(score_data)),
+                        timestamp: Utc::now(),
+                        valid: true,
+                        disqualified: false,
+                        notes,
+                        original_id: None,
+                        revision: 0,
+                        entered_by: None,
+                        entered_at: None,
+                        modified_by: None,
+                        modified_at: None,
+                        verification_state: Default::default(),
+                        verified_by: None,
+                        verified_at: None,
+                        judge_id: None,
+                    };
+
+                    if let Err(validation_error) = self.scores_service.validate(&score) {
+                        import_row.status = ScoreImportStatus::Invalid;
+                        import_row.errors = validation_error.errors;
+                        import_row.field_errors = validation_error.field_errors;
+                        import.invalid += 1;
+                    } else {
+                        import_row.status = ScoreImportStatus::Matched;
+                        import.matched += 1;
+                    }
+                    import_row.score = Some(score);
+                }
+                _ => {
+                    import_row.status = ScoreImportStatus::Invalid;
+                    import_row.errors.push(format!(
+                        "Team name {} is in multiple groups, add a group column to choose one",
+                        name.trim()
+                    ));
+                    import.invalid += 1;
+                }
+            }
+
+            import.rows.push(import_row);
+        }
+
+        Ok(import)
+    }
+
+    /// Creates the scores of a CSV file in a single transaction if every row can be imported,
+    /// otherwise returns the preview without creating anything
+    pub fn commit(
+        &self,
+        competition_event_id: Uuid,
+        csv: &str,
+        current_user: &DbUser,
+    ) -> Result<ScoreImport, GenericError> {
+        let mut import = self.preview(competition_event_id, csv)?;
+
+        if !import.is_importable() {
+            return Ok(import);
+        }
+
+        let scores: Vec<Score> = import
+            .rows
+            .iter()
+            .filter_map(|row| row.score.clone())
+            .collect();
+        let ids = self.scores_service.create_all(&scores, current_user)?;
+
+        for (row, id) in import.rows.iter_mut().zip(&ids) {
+            if let Some(score) = &mut row.score {
+                score.id = Some(*id);
+            }
+        }
+        import.created = Some(ids);
+
+        Ok(import)
+    }
+}
+
+/// Reads the header and rows of a CSV file, which must all have the same number of columns
+fn read_csv(csv: &str) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let mut reader = csv::Reader::from_reader(csv.trim_start_matches('\u{feff}').as_bytes());
+
+    let result = (|| {
+        let columns: Vec<String> = reader
+            .headers()?
+            .iter()
+            .map(|column| column.trim().to_owned())
+            .collect();
+        let rows = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(String::from).collect()))
+            .collect::<csv::Result<Vec<Vec<String>>>>()?;
+
+        Ok::<_, csv::Error>((columns, rows))
+    })();
+
+    result.map_err(|_| String::from("CSV file could not be read"))
+}
+
+/// Checks if a name in a CSV file matches a team or group name, ignoring case and surrounding whitespace
+fn names_match(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Checks if a team name and optional group name in a CSV file match a team and its group
+fn team_matches(team: &str, group: &str, name: &str, group_name: Option<&str>) -> bool {
+    names_match(team, name) && group_name.is_none_or(|group_name| names_match(group, group_name))
+}
+
+/// Converts a CSV value to a score data value, leaving anything that can't be converted for validation to report
+fn csv_value(field_type: &ScoreFieldType, value: &str) -> JsonValue {
+    let value = value.trim();
+    if value.is_empty() {
+        return JsonValue::Null;
+    }
+
+    match field_type {
+        ScoreFieldType::Boolean => match value.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => JsonValue::Bool(true),
+            "false" | "no" | "n" | "0" => JsonValue::Bool(false),
+            _ => JsonValue::String(value.to_owned()),
+        },
+        ScoreFieldType::Number | ScoreFieldType::Time => JsonValue::String(value.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_match_ignoring_case_and_whitespace() {
+        assert!(names_match("Red Team", "red team"));
+        assert!(names_match("  Red Team ", "RED TEAM"));
+        assert!(names_match("Équipe", "équipe"));
+        assert!(!names_match("Red Team", "RedTeam"));
+        assert!(!names_match("Red Team", "Red Team 2"));
+    }
+
+    #[test]
+    fn team_matches_group_if_given() {
+        assert!(team_matches("Red", "Group A", "red", None));
+        assert!(team_matches("Red", "Group A", "red", Some(" group a ")));
+        assert!(!team_matches("Red", "Group A", "red", Some("Group B")));
+        assert!(!team_matches("Red", "Group A", "Blue", Some("Group A")));
+    }
+
+    #[test]
+    fn read_csv_trims_headers_and_keeps_quoted_values() {
+        let (columns, rows) =
+            read_csv("\u{feff}Team , Points,Notes\nRed,5,\"late, but counted\"\nBlue,,\n").unwrap();
+
+        assert_eq!(columns, ["Team", "Points", "Notes"]);
+        assert_eq!(rows, [["Red", "5", "late, but counted"], ["Blue", "", ""]]);
+    }
+
+    #[test]
+    fn read_csv_rejects_rows_with_wrong_column_count() {
+        assert!(read_csv("Team,Points\nRed,5,extra\n").is_err());
+    }
+
+    #[test]
+    fn csv_values_are_converted_for_field_type() {
+        assert_eq!(
+            csv_value(&ScoreFieldType::Boolean, " Yes "),
+            JsonValue::Bool(true)
+        );
+        assert_eq!(
+            csv_value(&ScoreFieldType::Boolean, "0"),
+            JsonValue::Bool(false)
+        );
+        assert_eq!(
+            csv_value(&ScoreFieldType::Boolean, "maybe"),
+            JsonValue::String(String::from("maybe"))
+        );
+        assert_eq!(
+            csv_value(&ScoreFieldType::Number, " 1.50 "),
+            JsonValue::String(String::from("1.50"))
+        );
+        assert_eq!(csv_value(&ScoreFieldType::Time, ""), JsonValue::Null);
+    }
+}
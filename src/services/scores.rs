@@ -1,27 +1,215 @@
-use rusqlite::{named_params, OptionalExtension};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{named_params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::{
+    config::AppConfig,
     database::{
-        scores::{Score, ScoreType},
+        changes::ChangeAction,
+        competition_events::CompetitionEvent,
+        score_calculators::ScoreCalculator,
+        scores::{Score, ScoreType, VerificationState},
+        users::{DbUser, UserPermission},
         Database,
     },
-    helpers::errors::GenericError,
+    helpers::{errors::GenericError, json::decode_json_string},
+};
+
+use super::{
+    changes::ChangesService, competition_events::CompetitionEventsService, events::EventsService,
+    group_participation::GroupParticipationsService, groups::GroupsService,
+    judge_assignments::JudgeAssignmentsService, score_calculators::ScoreCalculatorsService,
+    season_competitions::SeasonCompetitionsService, teams::TeamsService,
 };
 
+/// Problems found when validating a score against its competition event and the score fields of its score calculator
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreValidationError {
+    /// Error messages for problems with the score as a whole, such as a team from a different season
+    pub errors: Vec<String>,
+    /// Error message for each field that is missing, unknown or of the wrong type
+    pub field_errors: BTreeMap<String, String>,
+}
+
+/// Revision of a score in its history, with what changed from the previous revision
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreRevision {
+    #[serde(flatten)]
+    pub score: Score,
+    /// Names of the score's fields that differ from the previous revision, empty for the first revision
+    pub changed_fields: Vec<String>,
+    /// Names of the score data fields that differ from the previous revision, empty for the first revision
+    pub changed_score_data: Vec<String>,
+}
+
+/// Time after which a claimed sync operation without a result can be claimed again by the same user,
+/// so an operation interrupted while being applied can be retried
+const SYNC_CLAIM_TIMEOUT_SECONDS: i64 = 300;
+
+/// Default and maximum number of scores listed at once
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Filters for listing scores, with unset fields matching all scores
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ScoreFilter {
+    pub season_id: Option<Uuid>,
+    pub season_competition_id: Option<Uuid>,
+    pub competition_event_id: Option<Uuid>,
+    /// Matches group scores of the group participation and team scores of its teams
+    pub group_participation_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+    pub score_type: Option<ScoreType>,
+    pub valid: Option<bool>,
+    pub disqualified: Option<bool>,
+    /// Inclusive start of the range of times the scores were entered
+    pub entered_after: Option<DateTime<Utc>>,
+    /// Exclusive end of the range of times the scores were entered
+    pub entered_before: Option<DateTime<Utc>>,
+    /// Matches scores entered, modified or judged by the user
+    pub user_id: Option<Uuid>,
+    pub entered_by: Option<Uuid>,
+    pub modified_by: Option<Uuid>,
+    pub judge_id: Option<Uuid>,
+}
+
+/// Field to sort listed scores by
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum ScoreSort {
+    Timestamp,
+    #[default]
+    EnteredAt,
+    ModifiedAt,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+/// Sorting and position of a page of listed scores
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ScorePagination {
+    #[serde(default)]
+    pub sort: ScoreSort,
+    #[serde(default)]
+    pub direction: SortDirection,
+    /// Cursor returned with the previous page, must be used with the same sorting and filters
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// A page of listed scores
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScorePage {
+    pub scores: Vec<Score>,
+    /// Cursor to get the next page, or none if this is the last page
+    pub cursor: Option<String>,
+}
+
+/// Action of a queued score operation submitted by an offline client
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ScoreSyncAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// Score operation queued by an offline client
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreSyncOperation {
+    /// Client-generated key so that retried operations are only applied once
+    pub idempotency_key: Uuid,
+    pub action: ScoreSyncAction,
+    /// Score ID, generated by the client when creating a score
+    pub id: Uuid,
+    /// Score data for creating or updating a score
+    pub score: Option<Score>,
+    /// Revision the client last saw, if the operation should conflict when the score was changed since then
+    pub revision: Option<i64>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ScoreSyncStatus {
+    Success,
+    /// The score was changed, deleted or locked on the server, or the operation is still being applied
+    Conflict,
+    Failed,
+}
+
+/// Result of applying a queued score operation, which is returned again if the operation is retried
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreSyncResult {
+    pub idempotency_key: Uuid,
+    pub id: Uuid,
+    pub status: ScoreSyncStatus,
+    pub error: Option<String>,
+    pub validation_error: Option<ScoreValidationError>,
+    /// Current server version of the score, if it exists
+    pub score: Option<Score>,
+}
+
+/// Position after the last score of a page, formatted as the score ID followed by the sort value
+struct ScoreCursor {
+    sort_key: String,
+    id: Uuid,
+}
+impl ScoreCursor {
+    fn parse(cursor: &str) -> Option<Self> {
+        let (id, sort_key) = cursor.split_once(' ')?;
+        Some(Self {
+            sort_key: String::from(sort_key),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+impl std::fmt::Display for ScoreCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.id, self.sort_key)
+    }
+}
+
 pub struct ScoresService {
     db: Database,
+    changes_service: ChangesService,
+    competition_events_service: CompetitionEventsService,
+    events_service: EventsService,
+    group_participations_service: GroupParticipationsService,
+    groups_service: GroupsService,
+    judge_assignments_service: JudgeAssignmentsService,
+    score_calculators_service: ScoreCalculatorsService,
+    season_competitions_service: SeasonCompetitionsService,
+    teams_service: TeamsService,
 }
 
 impl ScoresService {
-    pub fn new(database: &Database) -> Self {
+    pub fn new(database: &Database, config: &AppConfig) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
+            competition_events_service: CompetitionEventsService::new(database),
+            events_service: EventsService::new(database),
+            group_participations_service: GroupParticipationsService::new(database),
+            groups_service: GroupsService::new(database),
+            judge_assignments_service: JudgeAssignmentsService::new(database),
+            score_calculators_service: ScoreCalculatorsService::new(database, config),
+            season_competitions_service: SeasonCompetitionsService::new(database),
+            teams_service: TeamsService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<Score> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Score> {
         let result: Option<Score> = db
             .prepare_cached(&format!(
                 "SELECT * FROM ({}) WHERE \"id\" = :id;",
@@ -35,17 +223,108 @@ impl ScoresService {
         result
     }
 
-    pub fn list(&self) -> Vec<Score> {
+    /// Lists one page of current score revisions matching the filter
+    pub fn list(
+        &self,
+        filter: &ScoreFilter,
+        pagination: &ScorePagination,
+    ) -> Result<ScorePage, GenericError> {
+        let cursor = match &pagination.cursor {
+            Some(cursor) => Some(ScoreCursor::parse(cursor).ok_or(GenericError::BAD_REQUEST)?),
+            None => None,
+        };
+        let limit = pagination
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let sort_column = match pagination.sort {
+            ScoreSort::Timestamp => "timestamp",
+            ScoreSort::EnteredAt => "entered_at",
+            ScoreSort::ModifiedAt => "modified_at",
+        };
+        let (order, comparison) = match pagination.direction {
+            SortDirection::Ascending => ("ASC", ">"),
+            SortDirection::Descending => ("DESC", "<"),
+        };
+
         let db = self.db.get();
-        let result = db
-            .prepare_cached(&format!("SELECT * FROM ({});", Score::UNION_SELECT,))
+        let mut scores: Vec<(Score, String)> = db
+            .prepare_cached(&format!(
+                "SELECT \"scores\".*, \"scores\".\"{sort_column}\" AS \"sort_key\" FROM ({union_select}) AS \"scores\"
+INNER JOIN \"competition_events\" ON \"competition_events\".\"id\" = \"scores\".\"competition_event_id\"
+INNER JOIN \"season_competitions\" ON \"season_competitions\".\"id\" = \"competition_events\".\"season_competition_id\"
+LEFT JOIN \"teams\" ON \"scores\".\"score_type\" = 'team' AND \"teams\".\"id\" = \"scores\".\"subject_id\"
+WHERE (:season_id IS NULL OR \"season_competitions\".\"season_id\" = :season_id)
+AND (:season_competition_id IS NULL OR \"competition_events\".\"season_competition_id\" = :season_competition_id)
+AND (:competition_event_id IS NULL OR \"scores\".\"competition_event_id\" = :competition_event_id)
+AND (:group_participation_id IS NULL OR COALESCE(\"teams\".\"group_participation_id\", \"scores\".\"subject_id\") = :group_participation_id)
+AND (:team_id IS NULL OR (\"scores\".\"score_type\" = 'team' AND \"scores\".\"subject_id\" = :team_id))
+AND (:score_type IS NULL OR \"scores\".\"score_type\" = :score_type)
+AND (:valid IS NULL OR \"scores\".\"valid\" = :valid)
+AND (:disqualified IS NULL OR \"scores\".\"disqualified\" = :disqualified)
+AND (:entered_after IS NULL OR \"scores\".\"entered_at\" >= :entered_after)
+AND (:entered_before IS NULL OR \"scores\".\"entered_at\" < :entered_before)
+AND (:user_id IS NULL OR :user_id IN (\"scores\".\"entered_by\", \"scores\".\"modified_by\", \"scores\".\"judge_id\"))
+AND (:entered_by IS NULL OR \"scores\".\"entered_by\" = :entered_by)
+AND (:modified_by IS NULL OR \"scores\".\"modified_by\" = :modified_by)
+AND (:judge_id IS NULL OR \"scores\".\"judge_id\" = :judge_id)
+AND (:cursor_id IS NULL OR \"scores\".\"{sort_column}\" {comparison} :cursor_key OR (\"scores\".\"{sort_column}\" = :cursor_key AND \"scores\".\"id\" {comparison} :cursor_id))
+ORDER BY \"scores\".\"{sort_column}\" {order}, \"scores\".\"id\" {order}
+LIMIT :limit;",
+                union_select = Score::UNION_SELECT,
+            ))
             .unwrap()
-            .query_map(named_params! {}, |row| Ok(Score::from_row(row)))
-            .expect("Error occurred getting all scores from database")
+            .query_map(
+                named_params! {
+                    ":season_id": filter.season_id,
+                    ":season_competition_id": filter.season_competition_id,
+                    ":competition_event_id": filter.competition_event_id,
+                    ":group_participation_id": filter.group_participation_id,
+                    ":team_id": filter.team_id,
+                    ":score_type": filter.score_type,
+                    ":valid": filter.valid,
+                    ":disqualified": filter.disqualified,
+                    ":entered_after": filter.entered_after,
+                    ":entered_before": filter.entered_before,
+                    ":user_id": filter.user_id,
+                    ":entered_by": filter.entered_by,
+                    ":modified_by": filter.modified_by,
+                    ":judge_id": filter.judge_id,
+                    ":cursor_key": cursor.as_ref().map(|cursor| &cursor.sort_key),
+                    ":cursor_id": cursor.as_ref().map(|cursor| cursor.id),
+                    // one extra score is fetched to check if there is another page
+                    ":limit": limit + 1,
+                },
+                |row| {
+                    Ok((
+                        Score::from_row(row),
+                        row.get("sort_key")
+                            .expect("Failed to get value from database row"),
+                    ))
+                },
+            )
+            .expect("Error occurred listing scores from database")
             .map(|x| x.unwrap())
             .collect();
 
-        result
+        let cursor = if scores.len() as i64 > limit {
+            scores.truncate(limit as usize);
+            scores.last().map(|(score, sort_key)| {
+                ScoreCursor {
+                    sort_key: sort_key.clone(),
+                    id: score.id.unwrap(),
+                }
+                .to_string()
+            })
+        } else {
+            None
+        };
+
+        Ok(ScorePage {
+            scores: scores.into_iter().map(|(score, _)| score).collect(),
+            cursor,
+        })
     }
 
     pub fn list_in_competition_event(&self, competition_event_id: Uuid) -> Vec<Score> {
@@ -70,6 +349,29 @@ impl ScoresService {
         result
     }
 
+    /// Lists valid scores in a competition event that haven't been verified or rejected yet
+    pub fn list_awaiting_verification(&self, competition_event_id: Uuid) -> Vec<Score> {
+        let db = self.db.get();
+
+        let result = db
+            .prepare_cached(&format!(
+                "SELECT * FROM ({}) WHERE \"competition_event_id\" = :competition_event_id AND \"valid\" AND \"verification_state\" = 'entered' ORDER BY \"timestamp\";",
+                Score::UNION_SELECT
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":competition_event_id": competition_event_id,
+                },
+                |row| Ok(Score::from_row(row)),
+            )
+            .expect("Error occurred getting scores awaiting verification from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        result
+    }
+
     pub fn get_for_competition_event_group(
         &self,
         competition_event_id: Uuid,
@@ -124,15 +426,370 @@ impl ScoresService {
         result
     }
 
-    pub fn create(&self, score: &Score) -> Result<Uuid, GenericError> {
-        let id = Uuid::new_v4();
+    /// Checks that the score is consistent with its competition event and that its score data is valid
+    pub fn validate(&self, score: &Score) -> Result<(), ScoreValidationError> {
+        let Some(competition_event) = self
+            .competition_events_service
+            .get(score.competition_event_id)
+        else {
+            return Err(ScoreValidationError {
+                errors: vec![String::from("Competition event does not exist")],
+                field_errors: BTreeMap::new(),
+            });
+        };
 
-        let query = match score.score_type {
-            ScoreType::Group => "INSERT INTO \"group_scores\" (\"id\", \"competition_event_id\", \"group_participation_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\") VALUES (:id, :competition_event_id, :subject_id, :score_data, :timestamp, :valid, :disqualified, :notes);",
-            ScoreType::Team => "INSERT INTO \"team_scores\" (\"id\", \"competition_event_id\", \"team_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\") VALUES (:id, :competition_event_id, :subject_id, :score_data, :timestamp, :valid, :disqualified, :notes);",
+        let errors = self.check_consistency(score, &competition_event);
+        let field_errors = self.check_score_data(score, &competition_event);
+
+        if errors.is_empty() && field_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ScoreValidationError {
+                errors,
+                field_errors,
+            })
+        }
+    }
+
+    /// Checks that the score type matches the competition event, that the team/group participation is in the same season as the competition event,
+    /// and that they are enabled. Invalid scores can still be saved for disabled items so that they can be invalidated.
+    fn check_consistency(
+        &self,
+        score: &Score,
+        competition_event: &CompetitionEvent,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if score.score_type != competition_event.score_type {
+            errors.push(format!(
+                "Score type must be {} for this competition event",
+                match competition_event.score_type {
+                    ScoreType::Group => "group",
+                    ScoreType::Team => "team",
+                }
+            ));
+        }
+
+        let season_competition = self
+            .season_competitions_service
+            .get(competition_event.season_competition_id);
+        let event = self.events_service.get(competition_event.event_id);
+
+        let (subject_name, group_participation, team) = match score.score_type {
+            ScoreType::Group => (
+                "Group",
+                self.group_participations_service.get(score.subject_id),
+                None,
+            ),
+            ScoreType::Team => {
+                let team = self.teams_service.get(score.subject_id);
+                let group_participation = team.as_ref().and_then(|team| {
+                    self.group_participations_service
+                        .get(team.group_participation_id)
+                });
+                ("Team", group_participation, team)
+            }
+        };
+        let group = group_participation
+            .as_ref()
+            .and_then(|group_participation| self.groups_service.get(group_participation.group_id));
+
+        if group_participation.is_none() || (score.score_type == ScoreType::Team && team.is_none())
+        {
+            errors.push(format!("{} does not exist", subject_name));
+            return errors;
+        }
+
+        if let (Some(season_competition), Some(group_participation)) =
+            (&season_competition, &group_participation)
+        {
+            if season_competition.season_id != group_participation.season_id {
+                errors.push(format!(
+                    "{} is not in the same season as the competition event",
+                    subject_name
+                ));
+            }
+        }
+
+        if score.valid {
+            if !competition_event.enabled
+                || !season_competition.is_some_and(|season_competition| season_competition.enabled)
+                || !event.is_some_and(|event| event.enabled)
+            {
+                errors.push(String::from("Competition event is disabled"));
+            }
+            if team.is_some_and(|team| !team.enabled) {
+                errors.push(String::from("Team is disabled"));
+            }
+            if !group_participation.is_some_and(|group_participation| group_participation.enabled)
+                || !group.is_some_and(|group| group.enabled)
+            {
+                errors.push(String::from("Group is disabled"));
+            }
+        }
+
+        errors
+    }
+
+    /// Checks that the score data has a value of the correct type for each score field of the competition event's score calculator.
+    /// Invalid and disqualified scores aren't used in calculations, so they aren't checked.
+    fn check_score_data(
+        &self,
+        score: &Score,
+        competition_event: &CompetitionEvent,
+    ) -> BTreeMap<String, String> {
+        let mut field_errors = BTreeMap::new();
+
+        if !score.valid || score.disqualified {
+            return field_errors;
+        }
+
+        let score_calculator = competition_event
+            .score_calculator
+            .and_then(|score_calculator| self.score_calculators_service.get(score_calculator))
+            .unwrap_or_else(ScoreCalculator::get_default);
+        let Some(score_fields) = score_calculator.get_score_fields() else {
+            return field_errors;
         };
 
+        match decode_json_string(&score.score_data) {
+            JsonValue::Object(score_data) => {
+                for (name, field_type) in &score_fields {
+                    let value = score_data.get(name).unwrap_or(&JsonValue::Null);
+                    if let Err(message) = field_type.validate(value) {
+                        field_errors.insert(name.clone(), format!("{} {}", name, message));
+                    }
+                }
+                for name in score_data.keys() {
+                    if !score_fields.contains_key(name) {
+                        field_errors.insert(name.clone(), format!("{} is not a score field", name));
+                    }
+                }
+            }
+            _ => {
+                field_errors.insert(
+                    String::from("score_data"),
+                    String::from("Score data must be an object"),
+                );
+            }
+        }
+
+        field_errors
+    }
+
+    /// Gets a current or earlier revision of a score by id using an existing connection
+    fn get_revision_with(db: &Connection, id: Uuid) -> Option<Score> {
+        db.prepare_cached(&format!(
+            "SELECT * FROM ({}) WHERE \"id\" = :id;",
+            Score::REVISIONS_UNION_SELECT
+        ))
+        .unwrap()
+        .query_row(named_params! {":id": id}, |row| Ok(Score::from_row(row)))
+        .optional()
+        .expect("Error occurred getting score revision by id from database")
+    }
+
+    /// Lists all revisions of a score, oldest first, or `None` if the score doesn't exist.
+    /// The id may be of the score or of any of its earlier revisions.
+    /// Each revision includes the fields that changed from the revision before it.
+    pub fn history(&self, id: Uuid) -> Option<Vec<ScoreRevision>> {
         let db = self.db.get();
+
+        let revision = Self::get_revision_with(&db, id)?;
+        let original_id = revision.original_id.unwrap_or(id);
+
+        let revisions: Vec<Score> = db
+            .prepare_cached(&format!(
+                "SELECT * FROM ({}) WHERE \"id\" = :original_id OR \"original_id\" = :original_id ORDER BY \"revision\";",
+                Score::REVISIONS_UNION_SELECT
+            ))
+            .unwrap()
+            .query_map(
+                named_params! {
+                    ":original_id": original_id,
+                },
+                |row| Ok(Score::from_row(row)),
+            )
+            .expect("Error occurred getting score history from database")
+            .map(|x| x.unwrap())
+            .collect();
+
+        let mut result: Vec<ScoreRevision> = Vec::with_capacity(revisions.len());
+        for score in revisions {
+            let (changed_fields, changed_score_data) = match result.last() {
+                Some(previous) => (
+                    Self::changed_fields(&previous.score, &score),
+                    Self::changed_score_data(&previous.score, &score),
+                ),
+                None => (Vec::new(), Vec::new()),
+            };
+            result.push(ScoreRevision {
+                score,
+                changed_fields,
+                changed_score_data,
+            });
+        }
+
+        Some(result)
+    }
+
+    /// Gets the names of the editable fields that differ between two revisions of a score.
+    /// Validity isn't compared, as earlier revisions are always stored as invalid.
+    fn changed_fields(previous: &Score, score: &Score) -> Vec<String> {
+        [
+            (
+                "competition_event_id",
+                previous.competition_event_id != score.competition_event_id,
+            ),
+            ("subject_id", previous.subject_id != score.subject_id),
+            (
+                "score_data",
+                decode_json_string(&previous.score_data) != decode_json_string(&score.score_data),
+            ),
+            ("timestamp", previous.timestamp != score.timestamp),
+            ("disqualified", previous.disqualified != score.disqualified),
+            ("notes", previous.notes != score.notes),
+            ("judge_id", previous.judge_id != score.judge_id),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| String::from(name))
+        .collect()
+    }
+
+    /// Gets the names of the score data fields that were added, removed or changed between two revisions of a score
+    fn changed_score_data(previous: &Score, score: &Score) -> Vec<String> {
+        let previous = decode_json_string(&previous.score_data);
+        let current = decode_json_string(&score.score_data);
+        let (Some(previous), Some(current)) = (previous.as_object(), current.as_object()) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<String> = previous
+            .keys()
+            .chain(current.keys())
+            .filter(|name| previous.get(*name) != current.get(*name))
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Checks if scores in a competition event can be changed, which requires the lock override permission if it is locked
+    fn can_change_scores(&self, current_user: &DbUser, competition_event_id: Uuid) -> bool {
+        current_user.permissions & UserPermission::LOCK_OVERRIDE != 0
+            || !self
+                .competition_events_service
+                .is_locked(competition_event_id)
+    }
+
+    /// Checks if a user can set the judge of a score, which must be themselves, or left unchanged when updating,
+    /// unless they are a setup admin entering scores on behalf of other judges
+    fn can_set_judge(
+        current_user: &DbUser,
+        judge_id: Option<Uuid>,
+        existing_judge_id: Option<Uuid>,
+    ) -> bool {
+        current_user.permissions & UserPermission::SETUP_ADMIN != 0
+            || judge_id.is_none()
+            || judge_id == Some(current_user.id)
+            || judge_id == existing_judge_id
+    }
+
+    pub fn create(&self, score: &Score, current_user: &DbUser) -> Result<Uuid, GenericError> {
+        self.create_with_id(score, None, current_user)
+    }
+
+    /// Creates a score with the specified ID, or a new ID if not specified
+    fn create_with_id(
+        &self,
+        score: &Score,
+        id: Option<Uuid>,
+        current_user: &DbUser,
+    ) -> Result<Uuid, GenericError> {
+        self.check_create(score, current_user)?;
+
+        let id = id.unwrap_or_else(Uuid::new_v4);
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = self.insert(&transaction, score, id, current_user);
+
+        if success {
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    /// Creates multiple scores in a single transaction, so either all or none of them are created
+    pub fn create_all(
+        &self,
+        scores: &[Score],
+        current_user: &DbUser,
+    ) -> Result<Vec<Uuid>, GenericError> {
+        for score in scores {
+            self.check_create(score, current_user)?;
+        }
+
+        let ids: Vec<Uuid> = scores.iter().map(|_| Uuid::new_v4()).collect();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = scores
+            .iter()
+            .zip(&ids)
+            .all(|(score, id)| self.insert(&transaction, score, *id, current_user));
+
+        if success {
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(ids)
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
+    /// Checks that the user can create a score in its competition event for its judge and that the score is valid
+    fn check_create(&self, score: &Score, current_user: &DbUser) -> Result<(), GenericError> {
+        if !self
+            .judge_assignments_service
+            .can_enter_scores(current_user, score.competition_event_id)
+        {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !Self::can_set_judge(current_user, score.judge_id, None) {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !self.can_change_scores(current_user, score.competition_event_id) {
+            return Err(GenericError::CONFLICT);
+        }
+
+        if self.validate(score).is_err() {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a score and records it in the change feed, using the connection's transaction if it has one
+    fn insert(&self, db: &Connection, score: &Score, id: Uuid, current_user: &DbUser) -> bool {
+        let query = match score.score_type {
+            ScoreType::Group => "INSERT INTO \"group_scores\" (\"id\", \"competition_event_id\", \"group_participation_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"judge_id\") VALUES (:id, :competition_event_id, :subject_id, :score_data, :timestamp, :valid, :disqualified, :notes, :user_id, :now, :user_id, :now, :judge_id);",
+            ScoreType::Team => "INSERT INTO \"team_scores\" (\"id\", \"competition_event_id\", \"team_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"judge_id\") VALUES (:id, :competition_event_id, :subject_id, :score_data, :timestamp, :valid, :disqualified, :notes, :user_id, :now, :user_id, :now, :judge_id);",
+        };
+
         let success = db
             .prepare_cached(query)
             .unwrap()
@@ -145,63 +802,238 @@ impl ScoresService {
                 ":valid": score.valid,
                 ":disqualified": score.disqualified,
                 ":notes": score.notes,
+                ":user_id": current_user.id,
+                ":now": Utc::now(),
+                ":judge_id": score.judge_id.unwrap_or(current_user.id),
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                db,
+                "score",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(db, id),
+            );
+        }
+
+        success
+    }
+
+    /// Updates a score, keeping the previous values as an earlier revision that is marked as invalid.
+    /// The id of the score data is ignored in favour of `id`.
+    pub fn update(
+        &self,
+        id: Uuid,
+        score: &Score,
+        current_user: &DbUser,
+    ) -> Result<Uuid, GenericError> {
+        let Some(existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        if !self
+            .judge_assignments_service
+            .can_enter_scores(current_user, existing.competition_event_id)
+            || !self
+                .judge_assignments_service
+                .can_enter_scores(current_user, score.competition_event_id)
+        {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !Self::can_set_judge(current_user, score.judge_id, existing.judge_id) {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !self.can_change_scores(current_user, existing.competition_event_id)
+            || !self.can_change_scores(current_user, score.competition_event_id)
+        {
+            return Err(GenericError::CONFLICT);
+        }
+
+        // group and team scores are stored separately, so the revisions would be split between them
+        if existing.score_type != score.score_type {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        if self.validate(score).is_err() {
+            return Err(GenericError::BAD_REQUEST);
+        }
+
+        let (revision_query, update_query) = match score.score_type {
+            ScoreType::Group => (
+                "INSERT INTO \"group_scores\" (\"id\", \"competition_event_id\", \"group_participation_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\") SELECT :revision_id, \"competition_event_id\", \"group_participation_id\", \"score_data\", \"timestamp\", 0, \"disqualified\", \"notes\", \"id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"group_scores\" WHERE \"id\" = :id;",
+                "UPDATE \"group_scores\" SET \"competition_event_id\" = :competition_event_id, \"group_participation_id\" = :subject_id, \"score_data\" = :score_data, \"timestamp\" = :timestamp, \"valid\" = :valid, \"disqualified\" = :disqualified, \"notes\" = :notes, \"revision\" = \"revision\" + 1, \"modified_by\" = :modified_by, \"modified_at\" = :modified_at, \"verification_state\" = 'entered', \"verified_by\" = NULL, \"verified_at\" = NULL, \"judge_id\" = COALESCE(:judge_id, \"judge_id\") WHERE \"id\" = :id;",
+            ),
+            ScoreType::Team => (
+                "INSERT INTO \"team_scores\" (\"id\", \"competition_event_id\", \"team_id\", \"score_data\", \"timestamp\", \"valid\", \"disqualified\", \"notes\", \"original_id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\") SELECT :revision_id, \"competition_event_id\", \"team_id\", \"score_data\", \"timestamp\", 0, \"disqualified\", \"notes\", \"id\", \"revision\", \"entered_by\", \"entered_at\", \"modified_by\", \"modified_at\", \"verification_state\", \"verified_by\", \"verified_at\", \"judge_id\" FROM \"team_scores\" WHERE \"id\" = :id;",
+                "UPDATE \"team_scores\" SET \"competition_event_id\" = :competition_event_id, \"team_id\" = :subject_id, \"score_data\" = :score_data, \"timestamp\" = :timestamp, \"valid\" = :valid, \"disqualified\" = :disqualified, \"notes\" = :notes, \"revision\" = \"revision\" + 1, \"modified_by\" = :modified_by, \"modified_at\" = :modified_at, \"verification_state\" = 'entered', \"verified_by\" = NULL, \"verified_at\" = NULL, \"judge_id\" = COALESCE(:judge_id, \"judge_id\") WHERE \"id\" = :id;",
+            ),
+        };
+
+        let revision_id = Uuid::new_v4();
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(revision_query)
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":revision_id": revision_id,
+            })
+            .is_ok()
+            && transaction
+                .prepare_cached(update_query)
+                .unwrap()
+                .execute(named_params! {
+                    ":id": id,
+                    ":competition_event_id": score.competition_event_id,
+                    ":subject_id": score.subject_id,
+                    ":score_data": score.score_data,
+                    ":timestamp": score.timestamp,
+                    ":valid": score.valid,
+                    ":disqualified": score.disqualified,
+                    ":notes": score.notes,
+                    ":modified_by": current_user.id,
+                    ":modified_at": Utc::now(),
+                    ":judge_id": score.judge_id,
+                })
+                .is_ok();
+
+        if success {
+            // the earlier revision is a new row, so clients following the changes can keep the history up to date
+            self.changes_service.record(
+                &transaction,
+                "score",
+                ChangeAction::Create,
+                revision_id,
+                &Self::get_revision_with(&transaction, revision_id),
+            );
+            self.changes_service.record(
+                &transaction,
+                "score",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
 
-    pub fn update(&self, score: &Score) -> Result<Uuid, GenericError> {
-        let Some(_existing) = self.get(score.id.unwrap()) else {
+    /// Marks a score as verified or rejected.
+    /// Scores must be verified by a different user than the one who entered the current revision.
+    pub fn set_verification_state(
+        &self,
+        id: Uuid,
+        verification_state: VerificationState,
+        current_user: &DbUser,
+    ) -> Result<Uuid, GenericError> {
+        let Some(existing) = self.get(id) else {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let query = match score.score_type {
-            ScoreType::Group => "UPDATE \"group_scores\" SET \"competition_event_id\" = :competition_event_id, \"group_participation_id\" = :subject_id, \"score_data\" = :score_data, \"timestamp\" = :timestamp, \"valid\" = :valid, \"disqualified\" = :disqualified, \"notes\" = :notes WHERE \"id\" = :id;",
-            ScoreType::Team => "UPDATE \"team_scores\" SET \"competition_event_id\" = :competition_event_id, \"team_id\" = :subject_id, \"score_data\" = :score_data, \"timestamp\" = :timestamp, \"valid\" = :valid, \"disqualified\" = :disqualified, \"notes\" = :notes WHERE \"id\" = :id;",
+        if existing.modified_by == Some(current_user.id) {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !self.can_change_scores(current_user, existing.competition_event_id) {
+            return Err(GenericError::CONFLICT);
+        }
+
+        let query = match existing.score_type {
+            ScoreType::Group => "UPDATE \"group_scores\" SET \"verification_state\" = :verification_state, \"verified_by\" = :verified_by, \"verified_at\" = :verified_at WHERE \"id\" = :id;",
+            ScoreType::Team => "UPDATE \"team_scores\" SET \"verification_state\" = :verification_state, \"verified_by\" = :verified_by, \"verified_at\" = :verified_at WHERE \"id\" = :id;",
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(query)
             .unwrap()
             .execute(named_params! {
-                ":id": score.id,
-                ":competition_event_id": score.competition_event_id,
-                ":subject_id": score.subject_id,
-                ":score_data": score.score_data,
-                ":timestamp": score.timestamp,
-                ":valid": score.valid,
-                ":disqualified": score.disqualified,
-                ":notes": score.notes,
+                ":id": id,
+                ":verification_state": verification_state,
+                ":verified_by": current_user.id,
+                ":verified_at": Utc::now(),
             })
             .is_ok();
 
         if success {
-            Ok(score.id.unwrap())
+            self.changes_service.record(
+                &transaction,
+                "score",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
 
-    pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
-        let Some(_existing) = self.get(id) else {
+    pub fn delete(&self, id: Uuid, current_user: &DbUser) -> Result<(), GenericError> {
+        let Some(existing) = self.get(id) else {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let group_success = db
+        if !self
+            .judge_assignments_service
+            .can_enter_scores(current_user, existing.competition_event_id)
+        {
+            return Err(GenericError::FORBIDDEN);
+        }
+
+        if !self.can_change_scores(current_user, existing.competition_event_id) {
+            return Err(GenericError::CONFLICT);
+        }
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        // earlier revisions are deleted along with the score
+        let revision_ids: Vec<Uuid> = transaction
+            .prepare_cached(&format!(
+                "SELECT \"id\" FROM ({}) WHERE \"original_id\" = :id;",
+                Score::REVISIONS_UNION_SELECT
+            ))
+            .unwrap()
+            .query_map(named_params! {":id": id}, |row| row.get("id"))
+            .expect("Error occurred listing score revisions from database")
+            .map(|x| x.unwrap())
+            .collect();
+        // as are protests against the score
+        let protest_ids: Vec<Uuid> = transaction
+            .prepare_cached("SELECT \"id\" FROM \"protests\" WHERE \"group_score_id\" = :id OR \"team_score_id\" = :id;")
+            .unwrap()
+            .query_map(named_params! {":id": id}, |row| row.get("id"))
+            .expect("Error occurred listing protests against score from database")
+            .map(|x| x.unwrap())
+            .collect();
+        let group_success = transaction
             .prepare_cached("DELETE FROM \"group_scores\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": id,
             })
             .is_ok();
-        let team_success = db
+        let team_success = transaction
             .prepare_cached("DELETE FROM \"team_scores\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -210,9 +1042,188 @@ impl ScoresService {
             .is_ok();
 
         if group_success && team_success {
+            for protest_id in protest_ids {
+                self.changes_service
+                    .record_delete(&transaction, "protest", protest_id);
+            }
+            for revision_id in revision_ids {
+                self.changes_service
+                    .record_delete(&transaction, "score", revision_id);
+            }
+            self.changes_service
+                .record_delete(&transaction, "score", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
+
+    /// Applies score operations queued by an offline client, in order.
+    /// Each idempotency key is claimed before its operation is applied, so retried operations return the original result instead of being applied again.
+    /// Claims that never got a result expire, so an operation interrupted while being applied can be retried.
+    pub fn sync(
+        &self,
+        operations: &[ScoreSyncOperation],
+        current_user: &DbUser,
+    ) -> Vec<ScoreSyncResult> {
+        operations
+            .iter()
+            .map(|operation| self.sync_operation(operation, current_user))
+            .collect()
+    }
+
+    fn sync_operation(
+        &self,
+        operation: &ScoreSyncOperation,
+        current_user: &DbUser,
+    ) -> ScoreSyncResult {
+        // the connection is released before applying the operation, which checks out its own connections
+        let claimed = self
+            .db
+            .get()
+            .prepare_cached("INSERT INTO \"score_sync_operations\" (\"idempotency_key\", \"user_id\") VALUES (:idempotency_key, :user_id) ON CONFLICT (\"idempotency_key\") DO UPDATE SET \"timestamp\" = strftime('%Y-%m-%d %H:%M:%f+00:00') WHERE \"result\" IS NULL AND \"user_id\" = :user_id AND \"timestamp\" < strftime('%Y-%m-%d %H:%M:%f+00:00', 'now', :claim_timeout);")
+            .unwrap()
+            .execute(named_params! {
+                ":idempotency_key": operation.idempotency_key,
+                ":user_id": current_user.id,
+                ":claim_timeout": format!("-{} seconds", SYNC_CLAIM_TIMEOUT_SECONDS),
+            })
+            .expect("Error occurred claiming score sync operation")
+            == 1;
+
+        if !claimed {
+            let (user_id, result): (Uuid, Option<JsonValue>) = self
+                .db
+                .get()
+                .prepare_cached("SELECT \"user_id\", \"result\" FROM \"score_sync_operations\" WHERE \"idempotency_key\" = :idempotency_key;")
+                .unwrap()
+                .query_row(
+                    named_params! {":idempotency_key": operation.idempotency_key},
+                    |row| Ok((row.get("user_id")?, row.get("result")?)),
+                )
+                .expect("Error occurred getting score sync operation from database");
+
+            return match result {
+                Some(result) if user_id == current_user.id => serde_json::from_value(result)
+                    .expect("Error occurred deserializing score sync result"),
+                Some(_) => self.sync_result(
+                    operation,
+                    ScoreSyncStatus::Failed,
+                    Some(String::from(
+                        "Idempotency key was already used by another user",
+                    )),
+                    None,
+                ),
+                None => self.sync_result(
+                    operation,
+                    ScoreSyncStatus::Conflict,
+                    Some(String::from("Operation is already being applied")),
+                    None,
+                ),
+            };
+        }
+
+        let result = self.apply_sync_operation(operation, current_user);
+
+        self.db
+            .get()
+            .prepare_cached("UPDATE \"score_sync_operations\" SET \"result\" = :result WHERE \"idempotency_key\" = :idempotency_key;")
+            .unwrap()
+            .execute(named_params! {
+                ":idempotency_key": operation.idempotency_key,
+                ":result": serde_json::to_value(&result).expect("Error occurred serializing score sync result"),
+            })
+            .expect("Error occurred storing score sync result");
+
+        result
+    }
+    fn apply_sync_operation(
+        &self,
+        operation: &ScoreSyncOperation,
+        current_user: &DbUser,
+    ) -> ScoreSyncResult {
+        let existing = self.get(operation.id);
+
+        if let (Some(existing), Some(revision)) = (&existing, operation.revision) {
+            if existing.revision != revision {
+                return self.sync_result(
+                    operation,
+                    ScoreSyncStatus::Conflict,
+                    Some(String::from("Score was changed since it was last synced")),
+                    None,
+                );
+            }
+        }
+
+        let result = match operation.action {
+            ScoreSyncAction::Create | ScoreSyncAction::Update => {
+                let Some(score) = &operation.score else {
+                    return self.sync_result(
+                        operation,
+                        ScoreSyncStatus::Failed,
+                        Some(String::from("Score is required")),
+                        None,
+                    );
+                };
+                let mut score = score.clone();
+                score.id = Some(operation.id);
+
+                if let Err(validation_error) = self.validate(&score) {
+                    return self.sync_result(
+                        operation,
+                        ScoreSyncStatus::Failed,
+                        Some(GenericError::BAD_REQUEST.to_string()),
+                        Some(validation_error),
+                    );
+                }
+
+                match (operation.action, &existing) {
+                    (ScoreSyncAction::Create, None) => {
+                        self.create_with_id(&score, Some(operation.id), current_user)
+                    }
+                    (ScoreSyncAction::Create, Some(_)) => Err(GenericError::CONFLICT),
+                    _ => self.update(operation.id, &score, current_user),
+                }
+                .map(|_| ())
+            }
+            ScoreSyncAction::Delete => self.delete(operation.id, current_user),
+        };
+
+        match result {
+            Ok(()) => self.sync_result(operation, ScoreSyncStatus::Success, None, None),
+            // the score was deleted or changed by another user, or is locked
+            Err(err @ (GenericError::NOT_FOUND | GenericError::CONFLICT)) => self.sync_result(
+                operation,
+                ScoreSyncStatus::Conflict,
+                Some(err.to_string()),
+                None,
+            ),
+            Err(err) => self.sync_result(
+                operation,
+                ScoreSyncStatus::Failed,
+                Some(err.to_string()),
+                None,
+            ),
+        }
+    }
+
+    fn sync_result(
+        &self,
+        operation: &ScoreSyncOperation,
+        status: ScoreSyncStatus,
+        error: Option<String>,
+        validation_error: Option<ScoreValidationError>,
+    ) -> ScoreSyncResult {
+        ScoreSyncResult {
+            idempotency_key: operation.idempotency_key,
+            id: operation.id,
+            status,
+            error,
+            validation_error,
+            score: self.get(operation.id),
+        }
+    }
 }
@@ -1,24 +1,35 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{season_competitions::SeasonCompetition, Database},
+    database::{
+        changes::ChangeAction, judge_assignments::JudgeAssignment,
+        season_competitions::SeasonCompetition, Database,
+    },
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct SeasonCompetitionsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl SeasonCompetitionsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<SeasonCompetition> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<SeasonCompetition> {
         let result: Option<SeasonCompetition> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"season_competitions\" WHERE \"id\" = :id;",
@@ -72,10 +83,13 @@ impl SeasonCompetitionsService {
     pub fn create(&self, season_competition: &SeasonCompetition) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
-                "INSERT INTO \"season_competitions\" ({}) VALUES (:id, :season_id, :competition_id, :description, :score_calculator, :calculator_config, :enabled);",
+                "INSERT INTO \"season_competitions\" ({}) VALUES (:id, :season_id, :competition_id, :description, :score_calculator, :calculator_config, :enabled, :ranking_method, :ranking_direction, 0);",
                 SeasonCompetition::COLUMNS_SQL
             ))
             .unwrap()
@@ -87,10 +101,22 @@ impl SeasonCompetitionsService {
                 ":score_calculator": season_competition.score_calculator,
                 ":calculator_config": season_competition.calculator_config,
                 ":enabled": season_competition.enabled,
+                ":ranking_method": season_competition.ranking_method,
+                ":ranking_direction": season_competition.ranking_direction,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "season_competition",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -102,8 +128,11 @@ impl SeasonCompetitionsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"season_competitions\" SET \"season_id\" = :season_id, \"competition_id\" = :competition_id, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"season_competitions\" SET \"season_id\" = :season_id, \"competition_id\" = :competition_id, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled, \"ranking_method\" = :ranking_method, \"ranking_direction\" = :ranking_direction WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": season_competition.id,
@@ -113,23 +142,84 @@ impl SeasonCompetitionsService {
                 ":score_calculator": season_competition.score_calculator,
                 ":calculator_config": season_competition.calculator_config,
                 ":enabled": season_competition.enabled,
+                ":ranking_method": season_competition.ranking_method,
+                ":ranking_direction": season_competition.ranking_direction,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "season_competition",
+                ChangeAction::Update,
+                season_competition.id.unwrap(),
+                &Self::get_with(&transaction, season_competition.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(season_competition.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
 
+    /// Locks or unlocks the season competition, which prevents scores in its competition events from being changed
+    pub fn set_locked(&self, id: Uuid, locked: bool) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached(
+                "UPDATE \"season_competitions\" SET \"locked\" = :locked WHERE \"id\" = :id;",
+            )
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":locked": locked,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "season_competition",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
     pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
         let Some(_existing) = self.get(id) else {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        // judge assignments for the season competition are deleted along with it
+        self.changes_service.record_cascaded_deletes(
+            &transaction,
+            "judge_assignment",
+            JudgeAssignment::TABLE_NAME,
+            "season_competition_id",
+            id,
+        );
+        let success = transaction
             .prepare_cached("DELETE FROM \"season_competitions\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -138,6 +228,11 @@ impl SeasonCompetitionsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "season_competition", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
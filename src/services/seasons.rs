@@ -1,24 +1,32 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{seasons::Season, Database},
+    database::{changes::ChangeAction, seasons::Season, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct SeasonsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl SeasonsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<Season> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Season> {
         let result: Option<Season> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"seasons\" WHERE \"id\" = :id;",
@@ -48,10 +56,13 @@ impl SeasonsService {
     pub fn create(&self, season: &Season) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
-                "INSERT INTO \"seasons\" ({}) VALUES (:id, :name, :description, :score_calculator, :calculator_config, :enabled);",
+                "INSERT INTO \"seasons\" ({}) VALUES (:id, :name, :description, :score_calculator, :calculator_config, :enabled, :ranking_method, :ranking_direction, 0);",
                 Season::COLUMNS_SQL
             ))
             .unwrap()
@@ -62,10 +73,22 @@ impl SeasonsService {
                 ":score_calculator": season.score_calculator,
                 ":calculator_config": season.calculator_config,
                 ":enabled": season.enabled,
+                ":ranking_method": season.ranking_method,
+                ":ranking_direction": season.ranking_direction,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "season",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -77,8 +100,11 @@ impl SeasonsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"seasons\" SET \"name\" = :name, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"seasons\" SET \"name\" = :name, \"description\" = :description, \"score_calculator\" = :score_calculator, \"calculator_config\" = :calculator_config, \"enabled\" = :enabled, \"ranking_method\" = :ranking_method, \"ranking_direction\" = :ranking_direction WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": season.id,
@@ -87,23 +113,74 @@ impl SeasonsService {
                 ":score_calculator": season.score_calculator,
                 ":calculator_config": season.calculator_config,
                 ":enabled": season.enabled,
+                ":ranking_method": season.ranking_method,
+                ":ranking_direction": season.ranking_direction,
             })
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "season",
+                ChangeAction::Update,
+                season.id.unwrap(),
+                &Self::get_with(&transaction, season.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(season.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
         }
     }
 
+    /// Locks or unlocks the season, which prevents scores in its competition events from being changed
+    pub fn set_locked(&self, id: Uuid, locked: bool) -> Result<(), GenericError> {
+        let Some(_existing) = self.get(id) else {
+            return Err(GenericError::NOT_FOUND);
+        };
+
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
+            .prepare_cached("UPDATE \"seasons\" SET \"locked\" = :locked WHERE \"id\" = :id;")
+            .unwrap()
+            .execute(named_params! {
+                ":id": id,
+                ":locked": locked,
+            })
+            .is_ok();
+
+        if success {
+            self.changes_service.record(
+                &transaction,
+                "season",
+                ChangeAction::Update,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
+            Ok(())
+        } else {
+            Err(GenericError::BAD_REQUEST)
+        }
+    }
+
     pub fn delete(&self, id: Uuid) -> Result<(), GenericError> {
         let Some(_existing) = self.get(id) else {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"seasons\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -112,6 +189,11 @@ impl SeasonsService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "season", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
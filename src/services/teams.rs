@@ -1,24 +1,32 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use uuid::Uuid;
 
 use crate::{
-    database::{teams::Team, Database},
+    database::{changes::ChangeAction, teams::Team, Database},
     helpers::errors::GenericError,
 };
 
+use super::changes::ChangesService;
+
 pub struct TeamsService {
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl TeamsService {
     pub fn new(database: &Database) -> Self {
         Self {
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get(&self, id: Uuid) -> Option<Team> {
-        let db = self.db.get();
+        Self::get_with(&self.db.get(), id)
+    }
+
+    /// Gets by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_with(db: &Connection, id: Uuid) -> Option<Team> {
         let result: Option<Team> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"teams\" WHERE \"id\" = :id;",
@@ -67,8 +75,11 @@ impl TeamsService {
     pub fn create(&self, team: &Team) -> Result<Uuid, GenericError> {
         let id = Uuid::new_v4();
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached(&format!(
                 "INSERT INTO \"teams\" ({}) VALUES (:id, :group_participation_id, :name, :description, :enabled);",
                 Team::COLUMNS_SQL
@@ -84,6 +95,16 @@ impl TeamsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "team",
+                ChangeAction::Create,
+                id,
+                &Self::get_with(&transaction, id),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -95,8 +116,11 @@ impl TeamsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"teams\" SET \"group_participation_id\" = :group_participation_id, \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"teams\" SET \"group_participation_id\" = :group_participation_id, \"name\" = :name, \"description\" = :description, \"enabled\" = :enabled WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": team.id,
@@ -108,6 +132,16 @@ impl TeamsService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "team",
+                ChangeAction::Update,
+                team.id.unwrap(),
+                &Self::get_with(&transaction, team.id.unwrap()),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(team.id.unwrap())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -119,8 +153,11 @@ impl TeamsService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction
             .prepare_cached("DELETE FROM \"teams\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -129,6 +166,10 @@ impl TeamsService {
             .is_ok();
 
         if success {
+            self.changes_service.record_delete(&transaction, "team", id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -1,17 +1,19 @@
-use rusqlite::{named_params, OptionalExtension};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
     config::AppConfig,
     database::{
+        changes::ChangeAction,
+        judge_assignments::JudgeAssignment,
         users::{DbUser, UserPermission},
         Database,
     },
     helpers::errors::GenericError,
 };
 
-use super::auth::AuthService;
+use super::{auth::AuthService, changes::ChangesService};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct User {
@@ -27,6 +29,10 @@ pub struct User {
     pub permission_view_results: bool,
     pub permission_view_scores: bool,
     pub permission_enter_scores: bool,
+    #[serde(default)]
+    pub permission_verify_scores: bool,
+    #[serde(default)]
+    pub permission_override_locks: bool,
     pub permission_view_registration: bool,
     pub permission_enter_registration: bool,
 }
@@ -45,6 +51,8 @@ impl User {
             permission_view_results: user.permissions & UserPermission::RESULTS_VIEW != 0,
             permission_view_scores: user.permissions & UserPermission::SCORE_VIEW != 0,
             permission_enter_scores: user.permissions & UserPermission::SCORE_ENTRY != 0,
+            permission_verify_scores: user.permissions & UserPermission::SCORE_VERIFY != 0,
+            permission_override_locks: user.permissions & UserPermission::LOCK_OVERRIDE != 0,
             permission_view_registration: user.permissions & UserPermission::REGISTRATION_VIEW != 0,
             permission_enter_registration: user.permissions & UserPermission::REGISTRATION_ENTRY
                 != 0,
@@ -55,6 +63,7 @@ impl User {
 pub struct UsersService {
     config: AppConfig,
     db: Database,
+    changes_service: ChangesService,
 }
 
 impl UsersService {
@@ -62,12 +71,16 @@ impl UsersService {
         Self {
             config: config.clone(),
             db: database.clone(),
+            changes_service: ChangesService::new(database),
         }
     }
 
     pub fn get_user_by_id(&self, id: Uuid) -> Option<DbUser> {
-        let db = self.db.get();
+        Self::get_user_by_id_with(&self.db.get(), id)
+    }
 
+    /// Gets a user by id using an existing connection, so changes made in an uncommitted transaction on it are included
+    fn get_user_by_id_with(db: &Connection, id: Uuid) -> Option<DbUser> {
         let user_result: Option<DbUser> = db
             .prepare_cached(&format!(
                 "SELECT {} FROM \"users\" WHERE \"id\" = :id;",
@@ -126,8 +139,11 @@ impl UsersService {
 
         let permissions = Self::build_permissions(user);
 
-        let db = self.db.get();
-        let success = db.prepare_cached("INSERT INTO \"users\" (\"id\", \"username\", \"password\", \"description\", \"enabled\", \"permissions\") VALUES (:id, :username, :password, :description, :enabled, :permissions);")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("INSERT INTO \"users\" (\"id\", \"username\", \"password\", \"description\", \"enabled\", \"permissions\") VALUES (:id, :username, :password, :description, :enabled, :permissions);")
             .unwrap()
             .execute(named_params! {
                 ":id": user_id,
@@ -140,6 +156,17 @@ impl UsersService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "user",
+                ChangeAction::Create,
+                user_id,
+                &Self::get_user_by_id_with(&transaction, user_id)
+                    .map(|user| User::from_db_user(&user)),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(user_id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -162,8 +189,11 @@ impl UsersService {
 
         let permissions = Self::build_permissions(user);
 
-        let db = self.db.get();
-        let success = db.prepare_cached("UPDATE \"users\" SET \"username\" = :username, \"password\" = :password, \"description\" = :description, \"enabled\" = :enabled, \"permissions\" = :permissions WHERE \"id\" = :id;")
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        let success = transaction.prepare_cached("UPDATE \"users\" SET \"username\" = :username, \"password\" = :password, \"description\" = :description, \"enabled\" = :enabled, \"permissions\" = :permissions WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
                 ":id": user.id,
@@ -176,6 +206,17 @@ impl UsersService {
             .is_ok();
 
         if success {
+            self.changes_service.record(
+                &transaction,
+                "user",
+                ChangeAction::Update,
+                user_id,
+                &Self::get_user_by_id_with(&transaction, user_id)
+                    .map(|user| User::from_db_user(&user)),
+            );
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(user_id)
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -187,9 +228,19 @@ impl UsersService {
             return Err(GenericError::NOT_FOUND);
         };
 
-        let db = self.db.get();
-
-        let success = db
+        let mut db = self.db.get();
+        let transaction = db
+            .transaction()
+            .expect("Error occurred starting database transaction");
+        // the user's judge assignments are deleted along with them
+        self.changes_service.record_cascaded_deletes(
+            &transaction,
+            "judge_assignment",
+            JudgeAssignment::TABLE_NAME,
+            "user_id",
+            user.id,
+        );
+        let success = transaction
             .prepare_cached("DELETE FROM \"users\" WHERE \"id\" = :id;")
             .unwrap()
             .execute(named_params! {
@@ -198,6 +249,11 @@ impl UsersService {
             .is_ok();
 
         if success {
+            self.changes_service
+                .record_delete(&transaction, "user", user_id);
+            transaction
+                .commit()
+                .expect("Error occurred committing database transaction");
             Ok(())
         } else {
             Err(GenericError::BAD_REQUEST)
@@ -257,6 +313,14 @@ impl UsersService {
             UserPermission::SCORE_ENTRY
         } else {
             0
+        }) | (if user.permission_verify_scores {
+            UserPermission::SCORE_VERIFY
+        } else {
+            0
+        }) | (if user.permission_override_locks {
+            UserPermission::LOCK_OVERRIDE
+        } else {
+            0
         }) | (if user.permission_view_registration {
             UserPermission::REGISTRATION_VIEW
         } else {